@@ -0,0 +1,40 @@
+/*!
+Cancellation of a running solver
+
+A `CancellationToken` is a cheaply cloned handle on a shared flag. The solver checks its token
+between iterations, and hands it to the problem before the run so long running updates can poll it
+and return early.
+*/
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, Default)]
+/// Shared handle which stops a solver from any thread
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Generate a token which has not been cancelled
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Requests that every solver holding a clone of the token stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the token has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Cancels the token, returning whether this call was the one to do so
+    pub(crate) fn cancel_first(&self) -> bool {
+        self.cancelled
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }
+}
@@ -0,0 +1,128 @@
+/*!
+Checkpointing of the solver
+
+A checkpoint holds the whole `FixedPointSolver`, including the mixer's internal history, so a run
+resumed from it takes exactly the steps the uninterrupted run would have. Checkpoints are written
+to a temporary file which then replaces the previous checkpoint, so an interruption while writing
+leaves the last complete checkpoint in place.
+*/
+
+use crate::core::FixedPointError;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+/// How often checkpoints are written
+pub enum CheckpointFrequency {
+    /// After every given number of iterations
+    Every(u64),
+    /// After the first iteration ending once the given wall time has passed since the last write
+    Interval(instant::Duration),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+/// Serialisation format of the checkpoint file
+pub enum CheckpointFormat {
+    /// Human readable JSON
+    Json,
+    /// Compact binary encoding
+    Bincode,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Configuration for writing checkpoints during a run
+pub struct Checkpoint {
+    path: PathBuf,
+    frequency: CheckpointFrequency,
+    format: CheckpointFormat,
+
+    /// Internal data
+    #[serde(skip)]
+    last_write: Option<instant::Instant>,
+}
+
+impl Checkpoint {
+    /// Generate a checkpoint configuration writing JSON to the given path
+    pub fn new<T: AsRef<Path>>(path: T, frequency: CheckpointFrequency) -> Self {
+        Checkpoint {
+            path: path.as_ref().to_path_buf(),
+            frequency,
+            format: CheckpointFormat::Json,
+            last_write: None,
+        }
+    }
+
+    /// Factory method to set the serialisation format
+    pub fn format(mut self, format: CheckpointFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Returns the path the checkpoint is written to
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Starts the clock for interval checkpoints
+    pub(crate) fn start(&mut self) {
+        self.last_write = Some(instant::Instant::now());
+    }
+
+    /// Whether a checkpoint is due after the given iteration
+    pub(crate) fn is_due(&self, iter: u64) -> bool {
+        match self.frequency {
+            CheckpointFrequency::Every(n) => (n > 0) && iter.is_multiple_of(n),
+            CheckpointFrequency::Interval(interval) => self
+                .last_write
+                .is_none_or(|last| last.elapsed() >= interval),
+        }
+    }
+
+    /// Writes the value to the checkpoint path, replacing any previous checkpoint
+    pub(crate) fn write<T: Serialize>(&mut self, value: &T) -> Result<(), FixedPointError> {
+        let mut partial = self.path.clone().into_os_string();
+        partial.push(".partial");
+        let partial = PathBuf::from(partial);
+
+        let file = File::create(&partial).map_err(checkpoint_error)?;
+        let mut writer = BufWriter::new(file);
+        match self.format {
+            CheckpointFormat::Json => {
+                serde_json::to_writer(&mut writer, value).map_err(checkpoint_error)?
+            }
+            CheckpointFormat::Bincode => {
+                bincode::serialize_into(&mut writer, value).map_err(checkpoint_error)?
+            }
+        }
+        writer
+            .into_inner()
+            .map_err(checkpoint_error)?
+            .sync_all()
+            .map_err(checkpoint_error)?;
+        fs::rename(&partial, &self.path).map_err(checkpoint_error)?;
+
+        self.last_write = Some(instant::Instant::now());
+        Ok(())
+    }
+}
+
+/// Reads a value from a checkpoint file in the given format
+pub(crate) fn read_checkpoint<T: DeserializeOwned>(
+    path: &Path,
+    format: CheckpointFormat,
+) -> Result<T, FixedPointError> {
+    let reader = BufReader::new(File::open(path).map_err(checkpoint_error)?);
+    match format {
+        CheckpointFormat::Json => serde_json::from_reader(reader).map_err(checkpoint_error),
+        CheckpointFormat::Bincode => bincode::deserialize_from(reader).map_err(checkpoint_error),
+    }
+}
+
+/// Wraps an IO or serialisation error
+fn checkpoint_error<E: std::error::Error + Send + Sync + 'static>(e: E) -> FixedPointError {
+    FixedPointError::CheckpointFailed {
+        source: Box::new(e),
+    }
+}
@@ -0,0 +1,132 @@
+/*!
+Termination criteria
+
+Criteria are owned by the `FixedPointSolver` and checked before every iteration. Each criterion
+yields a specific `TerminationReason` when met, and criteria are combined with `Any` and `All`.
+*/
+
+use crate::core::{FPNorm, FPSub, FixedPointProblem, State, TerminationReason};
+use num::FromPrimitive;
+use std::sync::Arc;
+
+/// Closure deciding from the state whether the iteration should stop
+pub type CriterionFn<P> = Arc<dyn Fn(&State<P>) -> bool + Send + Sync>;
+
+/// A termination criterion, or a combination of criteria
+pub enum Criterion<P: FixedPointProblem> {
+    /// The cost falls below the given value
+    AbsoluteResidual(P::Float),
+    /// The cost falls below the given fraction of the cost after the first iteration
+    RelativeResidual(P::Float),
+    /// The norm of the last step falls below the given value, not checked right after a recovery
+    StepSize(P::Float),
+    /// The given number of iterations have been taken
+    MaxIterations(u64),
+    /// The given number of evaluations of the update have been made
+    MaxEvaluations(u64),
+    /// Another iteration, lasting as long as the last, would end past the given wall time
+    TimeLimit(instant::Duration),
+    /// The best cost has not improved for the given number of iterations
+    Stagnation(u64),
+    /// A user supplied closure returns true
+    Custom(CriterionFn<P>),
+    /// Any of the criteria is met, yielding the reason of the first which is
+    Any(Vec<Criterion<P>>),
+    /// All of the criteria are met, yielding the reason of the first
+    All(Vec<Criterion<P>>),
+}
+
+impl<P: FixedPointProblem> Clone for Criterion<P> {
+    fn clone(&self) -> Self {
+        match self {
+            Criterion::AbsoluteResidual(tol) => Criterion::AbsoluteResidual(*tol),
+            Criterion::RelativeResidual(tol) => Criterion::RelativeResidual(*tol),
+            Criterion::StepSize(tol) => Criterion::StepSize(*tol),
+            Criterion::MaxIterations(n) => Criterion::MaxIterations(*n),
+            Criterion::MaxEvaluations(n) => Criterion::MaxEvaluations(*n),
+            Criterion::TimeLimit(limit) => Criterion::TimeLimit(*limit),
+            Criterion::Stagnation(window) => Criterion::Stagnation(*window),
+            Criterion::Custom(f) => Criterion::Custom(f.clone()),
+            Criterion::Any(criteria) => Criterion::Any(criteria.clone()),
+            Criterion::All(criteria) => Criterion::All(criteria.clone()),
+        }
+    }
+}
+
+impl<P: FixedPointProblem> std::default::Default for Criterion<P> {
+    /// An absolute residual of 1e-6 or a thousand iterations
+    fn default() -> Self {
+        Criterion::Any(vec![
+            Criterion::AbsoluteResidual(P::Float::from_f64(1e-6).unwrap()),
+            Criterion::MaxIterations(1000),
+        ])
+    }
+}
+
+impl<P> Criterion<P>
+where
+    P: FixedPointProblem,
+    P::Param: FPSub<P::Param, P::Param> + FPNorm<P::Float>,
+{
+    /// Generate a criterion from a closure
+    pub fn custom<C>(criterion: C) -> Self
+    where
+        C: Fn(&State<P>) -> bool + Send + Sync + 'static,
+    {
+        Criterion::Custom(Arc::new(criterion))
+    }
+
+    /// Checks the criterion against the state
+    pub fn check(&self, state: &State<P>) -> TerminationReason {
+        let met = |condition: bool, reason| match condition {
+            true => reason,
+            false => TerminationReason::NotTerminated,
+        };
+        match self {
+            Criterion::AbsoluteResidual(tol) => {
+                met(state.cost < *tol, TerminationReason::ToleranceBeaten)
+            }
+            Criterion::RelativeResidual(tol) => met(
+                state.iter > 0 && state.cost < *tol * state.initial_cost,
+                TerminationReason::RelativeToleranceBeaten,
+            ),
+            Criterion::StepSize(tol) => met(
+                state.iter > 0
+                    && !state.recovered
+                    && state.param.sub(&state.prev_param).norm() < *tol,
+                TerminationReason::StepToleranceBeaten,
+            ),
+            Criterion::MaxIterations(n) => {
+                met(state.iter >= *n, TerminationReason::HitMaxIterations)
+            }
+            Criterion::MaxEvaluations(n) => met(
+                state.evaluations >= *n,
+                TerminationReason::HitMaxEvaluations,
+            ),
+            Criterion::TimeLimit(limit) => met(
+                state.time.unwrap_or_default() + state.iter_time.unwrap_or_default() > *limit,
+                TerminationReason::TimeLimitReached,
+            ),
+            Criterion::Stagnation(window) => met(
+                state.iter >= state.last_best_iter + *window,
+                TerminationReason::Stagnated,
+            ),
+            Criterion::Custom(f) => met(f(state), TerminationReason::UserCriterion),
+            Criterion::Any(criteria) => criteria
+                .iter()
+                .map(|c| c.check(state))
+                .find(|reason| reason.terminated())
+                .unwrap_or(TerminationReason::NotTerminated),
+            Criterion::All(criteria) => {
+                let reasons: Vec<_> = criteria.iter().map(|c| c.check(state)).collect();
+                match reasons.iter().all(|reason| reason.terminated()) {
+                    true => reasons
+                        .first()
+                        .copied()
+                        .unwrap_or(TerminationReason::NotTerminated),
+                    false => TerminationReason::NotTerminated,
+                }
+            }
+        }
+    }
+}
@@ -2,7 +2,10 @@
 This module defines the errors for the core crate
 */
 
-use miette::Diagnostic;
+use crate::core::{FixedPointProblem, State};
+use miette::{Diagnostic, Report};
+use num::traits::ToPrimitive;
+use std::fmt::{self, Debug, Display};
 use thiserror::Error;
 
 #[derive(Error, Diagnostic, Debug)]
@@ -16,26 +19,307 @@ pub enum FixedPointError {
     #[diagnostic(help("An error i have not forseen has occurred"), url(docsrs))]
     /// This error is called if the iteration terminates but should not have
     UnexpectedOutcome,
-    #[error("Exceeded maximum iterations")]
+    #[error("Failed to evaluate update at iteration {iteration}, parameter norm {param_norm}")]
     #[diagnostic(
-        help("Maybe try to relax your target tolerance, or adjust solver parameters"),
+        help("Evaluating the update function failed, check in the upstream crate"),
         url(docsrs)
     )]
-    /// Error to warn when the iteration exceeds the maximum prescribed number
-    /// The current cost is attached so as the degree of failure can be assessed
-    TooManyIterations(f64),
-    #[error("Failed to evaluate update")]
+    /// Error to warn when evaluation of the update, defined in the calling crate, fails
+    /// The error returned by the update is attached as the source
+    UpdateFailed {
+        /// Error returned by the update
+        source: SourceDiagnostic,
+        /// Iteration at which the update failed
+        iteration: u64,
+        /// Norm of the parameter passed to the update
+        param_norm: f64,
+    },
+    #[error(
+        "Failed to evaluate error vector at iteration {iteration}, parameter norm {param_norm}"
+    )]
     #[diagnostic(
-        help("Evaluating the update function failed, check in the upstream crate"),
+        help("Evaluating the problem's error vector failed, check in the upstream crate"),
         url(docsrs)
     )]
-    /// Error to warn when evaluation of the update, defined in the calling crate, fails
-    UpdateFailed,
-    #[error("Solution diverged")]
+    /// Error to warn when the error vector, defined in the calling crate, fails
+    /// The error returned by the error vector is attached as the source
+    ErrorVectorFailed {
+        /// Error returned by the error vector
+        source: SourceDiagnostic,
+        /// Iteration at which the error vector failed
+        iteration: u64,
+        /// Norm of the parameter passed to the error vector
+        param_norm: f64,
+    },
+    #[error("Solution diverged at iteration {iteration}")]
     #[diagnostic(
         help("The solution now contains NaN values, indicating it has overflown"),
         url(docsrs)
     )]
     /// Error to warn when the solution vector has overflown
-    NumericalDivergence,
+    NumericalDivergence {
+        /// Iteration at which NaN values appeared
+        iteration: u64,
+    },
+    #[error("Wrapped mixer {mixer} failed at iteration {iteration}")]
+    #[diagnostic(
+        help("The mixer wrapped by a globalisation strategy failed, see the cause below"),
+        url(docsrs)
+    )]
+    /// Error to warn when a mixer wrapped by another fails
+    /// The error of the wrapped mixer is attached as the source
+    MixerFailed {
+        /// Name of the wrapping mixer
+        mixer: String,
+        /// Iteration at which the wrapped mixer failed
+        iteration: u64,
+        /// Error returned by the wrapped mixer
+        source: Box<FixedPointError>,
+    },
+    #[error("Spectral bounds unsuitable for Chebyshev acceleration")]
+    #[diagnostic(
+        help("The eigenvalues of the linear part of the update must lie below one"),
+        url(docsrs)
+    )]
+    /// Error to warn when the supplied or estimated spectral bounds are invalid
+    InvalidSpectralBounds,
+    #[error("Update is not affine")]
+    #[diagnostic(
+        help("Krylov solvers require an update of the form A x + b, try a mixer instead"),
+        url(docsrs)
+    )]
+    /// Error to warn when a solver for affine problems is applied to a non-affine update
+    NonAffineUpdate,
+    #[error("No viable configuration")]
+    #[diagnostic(
+        help("Every trial failed or made no evaluations, try a wider search space or budget"),
+        url(docsrs)
+    )]
+    /// Error to warn when no trial configuration completed during tuning
+    NoViableConfiguration,
+    #[error("Evaluation budget of {budget} updates spent")]
+    #[diagnostic(
+        help("The tuning trial is stopped once its budget is spent, this is not a failure"),
+        url(docsrs)
+    )]
+    /// Error returned by a budgeted problem asked for an update beyond its budget
+    BudgetExhausted {
+        /// Number of updates allowed
+        budget: usize,
+    },
+    #[error("Checkpoint failed")]
+    #[diagnostic(
+        help("Check the checkpoint path is writable and the file matches the solver type"),
+        url(docsrs)
+    )]
+    /// Error to warn when a checkpoint could not be written or read
+    /// The underlying IO or serialisation error is attached as the source
+    CheckpointFailed {
+        /// IO or serialisation error which stopped the checkpoint
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[error("Termination check failed at iteration {iteration}")]
+    #[diagnostic(
+        help("The mixer could not decide whether to stop, see the cause below"),
+        url(docsrs)
+    )]
+    /// Error to warn when a mixer's termination check fails
+    /// The error returned by the check is attached as the source
+    TerminationCheckFailed {
+        /// Error returned by the termination check
+        source: SourceDiagnostic,
+        /// Iteration at which the check failed
+        iteration: u64,
+    },
+}
+
+impl FixedPointError {
+    /// Wraps the error returned by a failing update with where it happened
+    pub fn update_failed<F: ToPrimitive>(source: Report, iteration: u64, param_norm: F) -> Self {
+        FixedPointError::UpdateFailed {
+            source: source.into(),
+            iteration,
+            param_norm: param_norm.to_f64().unwrap_or(f64::NAN),
+        }
+    }
+
+    /// Wraps the error returned by a failing error vector with where it happened
+    pub fn error_vector_failed<F: ToPrimitive>(
+        source: Report,
+        iteration: u64,
+        param_norm: F,
+    ) -> Self {
+        FixedPointError::ErrorVectorFailed {
+            source: source.into(),
+            iteration,
+            param_norm: param_norm.to_f64().unwrap_or(f64::NAN),
+        }
+    }
+}
+
+/// Diagnostic returned from outside the crate, kept as the source of a `FixedPointError`
+///
+/// Reports render the message of the diagnostic in their chain of causes, and its code, help and
+/// labels remain available through the `Diagnostic` implementation
+pub struct SourceDiagnostic(Report);
+
+impl SourceDiagnostic {
+    /// Returns the report, which can be downcast to the original error type
+    pub fn get_report(&self) -> &Report {
+        &self.0
+    }
+
+    /// The diagnostic held by the report
+    fn diagnostic(&self) -> &(dyn Diagnostic + Send + Sync + 'static) {
+        &*self.0
+    }
+}
+
+impl From<Report> for SourceDiagnostic {
+    fn from(report: Report) -> Self {
+        SourceDiagnostic(report)
+    }
+}
+
+impl Debug for SourceDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.diagnostic(), f)
+    }
+}
+
+impl Display for SourceDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self.diagnostic(), f)
+    }
+}
+
+impl std::error::Error for SourceDiagnostic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.diagnostic().source()
+    }
+}
+
+impl Diagnostic for SourceDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.diagnostic().code()
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        self.diagnostic().severity()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.diagnostic().help()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.diagnostic().url()
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.diagnostic().source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        self.diagnostic().labels()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.diagnostic().related()
+    }
+}
+
+/// Error returned by a run of the solver, holding the state when the error occurred
+///
+/// The state keeps the best parameter and cost found before the failure, so the work done is not
+/// lost. Reports render the inner error, which the `?` operator converts to
+pub struct SolverError<P: FixedPointProblem> {
+    error: FixedPointError,
+    state: State<P>,
+}
+
+impl<P: FixedPointProblem> SolverError<P> {
+    /// Generate the error from the cause and the state of the solver
+    pub fn new(error: FixedPointError, state: State<P>) -> Self {
+        SolverError { error, state }
+    }
+
+    /// Returns the cause of the failure
+    pub fn get_error(&self) -> &FixedPointError {
+        &self.error
+    }
+
+    /// Returns the state of the solver when it failed
+    pub fn get_state(&self) -> &State<P> {
+        &self.state
+    }
+
+    /// Returns the best parameter found before the failure
+    pub fn get_best_param(&self) -> &P::Param {
+        &self.state.best_param
+    }
+
+    /// Discards the state, returning the cause of the failure
+    pub fn into_error(self) -> FixedPointError {
+        self.error
+    }
+}
+
+impl<P: FixedPointProblem> From<SolverError<P>> for FixedPointError {
+    fn from(error: SolverError<P>) -> Self {
+        error.error
+    }
+}
+
+impl<P: FixedPointProblem> Debug for SolverError<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SolverError")
+            .field("error", &self.error)
+            .field("iteration", &self.state.iter)
+            .field("best_cost", &self.state.best_cost.to_f64())
+            .finish()
+    }
+}
+
+impl<P: FixedPointProblem> Display for SolverError<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.error, f)
+    }
+}
+
+impl<P: FixedPointProblem> std::error::Error for SolverError<P> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+impl<P: FixedPointProblem> Diagnostic for SolverError<P> {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.code()
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        self.error.severity()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.help()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.error.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.error.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        self.error.labels()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.error.related()
+    }
 }
@@ -0,0 +1,39 @@
+use crate::core::math::FPDimension;
+use ndarray::Array1;
+
+impl<T> FPDimension for Array1<T> {
+    #[inline]
+    fn dimension(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+    use paste::item;
+
+    macro_rules! make_test {
+        ($t:ty) => {
+            item! {
+                #[test]
+                fn [<test_dimension_ $t>]() {
+                    let a: Array1<$t> = array![1 as $t, 4 as $t, 8 as $t];
+                    assert_eq!(a.dimension(), 3);
+                }
+            }
+        };
+    }
+
+    make_test!(i8);
+    make_test!(u8);
+    make_test!(i16);
+    make_test!(u16);
+    make_test!(i32);
+    make_test!(u32);
+    make_test!(i64);
+    make_test!(u64);
+    make_test!(f32);
+    make_test!(f64);
+}
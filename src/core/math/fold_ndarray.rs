@@ -0,0 +1,46 @@
+use crate::core::math::FPFoldValues;
+use ndarray::Array1;
+
+impl<T> FPFoldValues<T> for Array1<T> {
+    #[inline]
+    fn fold_values<A, G: FnMut(A, &T) -> A>(&self, init: A, f: G) -> A {
+        self.iter().fold(init, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+    use paste::item;
+
+    macro_rules! make_test {
+        ($t:ty) => {
+            item! {
+                #[test]
+                fn [<test_fold_values_ $t>]() {
+                    let a: Array1<$t> = array![1 as $t, 4 as $t, 8 as $t];
+                    let res = a.fold_values(vec![], |mut acc, x| {
+                        acc.push(*x);
+                        acc
+                    });
+                    let target = [1 as $t, 4 as $t, 8 as $t];
+                    for i in 0..3 {
+                        assert!(((target[i] - res[i]) as f64).abs() < f64::EPSILON);
+                    }
+                }
+            }
+        };
+    }
+
+    make_test!(i8);
+    make_test!(u8);
+    make_test!(i16);
+    make_test!(u16);
+    make_test!(i32);
+    make_test!(u32);
+    make_test!(i64);
+    make_test!(u64);
+    make_test!(f32);
+    make_test!(f64);
+}
@@ -0,0 +1,55 @@
+use crate::core::math::FPGather;
+use ndarray::Array1;
+
+impl<T> FPGather<T> for Array1<T>
+where
+    T: Clone,
+{
+    #[inline]
+    fn gather(&self, indices: &[usize]) -> Vec<T> {
+        indices.iter().map(|&i| self[i].clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+    use paste::item;
+
+    macro_rules! make_test {
+        ($t:ty) => {
+            item! {
+                #[test]
+                fn [<test_gather_ $t>]() {
+                    let a: Array1<$t> = array![1 as $t, 4 as $t, 8 as $t];
+                    let res = a.gather(&[2, 0]);
+                    let target = [8 as $t, 1 as $t];
+                    for i in 0..2 {
+                        assert!(((target[i] - res[i]) as f64).abs() < f64::EPSILON);
+                    }
+                }
+            }
+
+            item! {
+                #[test]
+                #[should_panic]
+                fn [<test_gather_panic_ $t>]() {
+                    let a: Array1<$t> = array![1 as $t, 4 as $t, 8 as $t];
+                    a.gather(&[3]);
+                }
+            }
+        };
+    }
+
+    make_test!(i8);
+    make_test!(u8);
+    make_test!(i16);
+    make_test!(u16);
+    make_test!(i32);
+    make_test!(u32);
+    make_test!(i64);
+    make_test!(u64);
+    make_test!(f32);
+    make_test!(f64);
+}
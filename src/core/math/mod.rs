@@ -4,11 +4,14 @@ operate with generic math libraries
 */
 mod add;
 mod add_ndarray;
+mod dimension_ndarray;
 mod div;
 mod div_ndarray;
 mod dot_ndarray;
 mod empty_ndarray;
 mod eye_ndarray;
+mod fold_ndarray;
+mod gather_ndarray;
 mod holds_nan_ndarray;
 mod into_2d_ndarray;
 mod into_f64;
@@ -25,11 +28,13 @@ mod zeros_ndarray;
 
 pub use crate::core::math::add::*;
 pub use crate::core::math::add_ndarray::*;
+pub use crate::core::math::dimension_ndarray::*;
 pub use crate::core::math::div::*;
 pub use crate::core::math::div_ndarray::*;
 pub use crate::core::math::dot_ndarray::*;
 pub use crate::core::math::empty_ndarray::*;
 pub use crate::core::math::eye_ndarray::*;
+pub use crate::core::math::gather_ndarray::*;
 pub use crate::core::math::holds_nan_ndarray::*;
 pub use crate::core::math::into_2d_ndarray::*;
 pub use crate::core::math::into_f64::*;
@@ -117,6 +122,24 @@ pub trait FPNorm<X> {
     fn norm(&self) -> X;
 }
 
+/// Number of elements in 'self'
+pub trait FPDimension {
+    /// Number of elements in 'self'
+    fn dimension(&self) -> usize;
+}
+
+/// Gathers the elements of 'self' at the given indices
+pub trait FPGather<X> {
+    /// Gathers the elements of 'self' at the given indices
+    fn gather(&self, indices: &[usize]) -> Vec<X>;
+}
+
+/// Folds over the elements of 'self' in order
+pub trait FPFoldValues<X> {
+    /// Folds over the elements of 'self' in order
+    fn fold_values<A, G: FnMut(A, &X) -> A>(&self, init: A, f: G) -> A;
+}
+
 /// Checks whether the quantity holds any nan
 pub trait FPHoldsNaN {
     /// L2 norm of self
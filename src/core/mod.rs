@@ -4,9 +4,15 @@ Node fixed point iteration tools core module
 This crate holds the core functionality of the library.
 */
 
+mod cancel;
+mod checkpoint;
+mod criteria;
 /// Error Handling
 mod errors;
 mod math;
+mod observer;
+mod portfolio;
+mod recovery;
 mod solver;
 mod state;
 
@@ -16,8 +22,14 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::fmt::{Debug, Display};
 
+pub use cancel::*;
+pub use checkpoint::*;
+pub use criteria::*;
 pub use errors::*;
 pub use math::*;
+pub use observer::*;
+pub use portfolio::*;
+pub use recovery::*;
 pub use solver::*;
 pub use state::*;
 
@@ -55,6 +67,24 @@ pub trait FixedPointProblem {
     fn update(&mut self, _values: &Self::Param) -> Result<Self::Param> {
         Err(FixedPointError::UnimplementedOperation.into())
     }
+
+    /// Returns the error vector for a parameter and its update
+    ///
+    /// Mixers which extrapolate on error vectors, such as DIIS, use the residual
+    /// `output - values` when this returns `None`
+    fn error_vector(
+        &mut self,
+        _values: &Self::Param,
+        _output: &Self::Param,
+    ) -> Result<Option<Self::Param>> {
+        Ok(None)
+    }
+
+    /// Receives the solver's cancellation token before the run starts
+    ///
+    /// Problems with long running updates can keep the token and return an error from `update`
+    /// once it is cancelled, which the solver reports as a cancellation rather than a failure
+    fn set_cancellation_token(&mut self, _token: CancellationToken) {}
 }
 
 /// This trait defines the mixer operation. All mixers implement the trait
@@ -65,6 +95,22 @@ pub trait Mixer<P: FixedPointProblem>: Serialize {
     /// Defines a single iteration of the mixing operation
     fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError>;
 
-    /// Checks whether termination conditions are satisfied
-    fn terminate(&mut self, state: &State<P>) -> Result<TerminationReason>;
+    /// Checks whether termination conditions specific to the mixer are satisfied
+    ///
+    /// General criteria such as tolerances and iteration limits are owned by the solver, so
+    /// mixers without conditions of their own need not override this
+    fn terminate(&mut self, _state: &State<P>) -> Result<TerminationReason> {
+        Ok(TerminationReason::NotTerminated)
+    }
+
+    /// Discards any history accumulated by the mixer
+    ///
+    /// After a reset the next iteration restarts from the parameter held in the `State`
+    fn reset(&mut self) {}
+
+    /// Scales the mixing parameter by the factor, making the mixer less aggressive
+    ///
+    /// Called by the solver after recovering from divergence, if damping is configured. Mixers
+    /// without a mixing parameter ignore this
+    fn dampen(&mut self, _factor: P::Float) {}
 }
@@ -0,0 +1,59 @@
+/*!
+Observers of the fixed point iteration
+
+Observers registered on a `FixedPointSolver` are notified when the run starts, after every
+iteration and when the run finishes, and may ask the solver to stop after any iteration.
+*/
+
+use crate::core::{FixedPointProblem, IterData, State};
+use std::sync::{Arc, Mutex, PoisonError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Decision returned by an observer after each iteration
+pub enum ObserverAction {
+    /// Continue the iteration
+    Continue,
+    /// Stop the iteration after the current step
+    Terminate,
+}
+
+/// This trait needs to be implemented by types monitoring the progress of a solver
+///
+/// All methods default to doing nothing, so only the events of interest need implementing
+pub trait Observer<P: FixedPointProblem> {
+    /// Called once before the first iteration, with the name of the mixer
+    fn observe_init(&mut self, _name: &str, _state: &State<P>) {}
+
+    /// Called after every iteration with the updated state and the mixer's output
+    fn observe_iter(&mut self, _state: &State<P>, _data: &IterData<P>) -> ObserverAction {
+        ObserverAction::Continue
+    }
+
+    /// Called once when the run finishes
+    fn observe_final(&mut self, _state: &State<P>) {}
+}
+
+/// Shared observers, so the caller can keep a handle to read back what was observed
+impl<P, O> Observer<P> for Arc<Mutex<O>>
+where
+    P: FixedPointProblem,
+    O: Observer<P>,
+{
+    fn observe_init(&mut self, name: &str, state: &State<P>) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .observe_init(name, state)
+    }
+
+    fn observe_iter(&mut self, state: &State<P>, data: &IterData<P>) -> ObserverAction {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .observe_iter(state, data)
+    }
+
+    fn observe_final(&mut self, state: &State<P>) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .observe_final(state)
+    }
+}
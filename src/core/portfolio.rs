@@ -0,0 +1,206 @@
+/*!
+Portfolio driver running several mixers on the same problem concurrently
+
+Each contender is a `FixedPointSolver` working on its own clone of the problem in a separate
+thread. The contenders share a cancellation token: the first contender to meet a convergence
+criterion wins and cancels the token, stopping the others after their current iteration, and a
+report records how far every contender got.
+*/
+
+use crate::core::{
+    CancellationToken, FPIntof64, FPNorm, FPSub, FixedPointProblem, FixedPointResult,
+    FixedPointSolver, Mixer, TerminationReason,
+};
+use serde::{Deserialize, Serialize};
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// How a contender in the portfolio finished
+pub enum ContenderOutcome {
+    /// The contender met a residual or step tolerance
+    Converged,
+    /// The contender met another of its termination criteria, such as an iteration limit
+    Stopped(TerminationReason),
+    /// The contender was stopped early, because another contender converged first, the portfolio
+    /// was cancelled or an observer asked it to
+    Cancelled,
+    /// The contender returned an error, whose message is attached
+    Failed(String),
+    /// The contender panicked during an iteration
+    Panicked,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Summary of the progress made by a single contender
+pub struct ContenderReport {
+    /// Name given to the contender
+    pub name: String,
+    /// Number of iterations completed
+    pub iterations: u64,
+    /// Cost at the last completed iteration
+    pub cost: f64,
+    /// Best cost reached
+    pub best_cost: f64,
+    /// How the contender finished
+    pub outcome: ContenderOutcome,
+}
+
+/// Output of a single contender thread
+struct ContenderRun<P: FixedPointProblem> {
+    report: ContenderReport,
+    result: Option<FixedPointResult<P>>,
+    won: bool,
+}
+
+type ContenderFn<'a, P> = Box<dyn FnOnce(P, &CancellationToken) -> ContenderRun<P> + Send + 'a>;
+
+/// Runs several solvers on clones of a problem, keeping the first to converge
+pub struct Portfolio<'a, P: FixedPointProblem> {
+    contenders: Vec<ContenderFn<'a, P>>,
+    cancellation: CancellationToken,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+/// Data type for the result of a portfolio run
+pub struct PortfolioResult<P: FixedPointProblem> {
+    /// Index of the winning contender
+    winner: Option<usize>,
+
+    /// Result of the winning contender
+    result: Option<FixedPointResult<P>>,
+
+    /// Progress of every contender, in the order they were added
+    reports: Vec<ContenderReport>,
+}
+
+impl<P: FixedPointProblem> PortfolioResult<P> {
+    /// Returns the index of the contender which converged first
+    pub fn get_winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    /// Returns the result of the contender which converged first
+    pub fn get_result(&self) -> Option<&FixedPointResult<P>> {
+        self.result.as_ref()
+    }
+
+    /// Returns the reports of all contenders, in the order they were added
+    pub fn get_reports(&self) -> &[ContenderReport] {
+        &self.reports
+    }
+}
+
+impl<'a, P> std::default::Default for Portfolio<'a, P>
+where
+    P: FixedPointProblem + Clone + Send + 'a,
+    P::Param: FPSub<P::Param, P::Param> + FPNorm<P::Float> + Send,
+    P::Float: FPIntof64 + Send,
+{
+    fn default() -> Self {
+        Portfolio::new()
+    }
+}
+
+impl<'a, P> Portfolio<'a, P>
+where
+    P: FixedPointProblem + Clone + Send + 'a,
+    P::Param: FPSub<P::Param, P::Param> + FPNorm<P::Float> + Send,
+    P::Float: FPIntof64 + Send,
+{
+    /// Generate an empty portfolio
+    pub fn new() -> Self {
+        Portfolio {
+            contenders: vec![],
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// Returns a handle which stops every contender when cancelled
+    pub fn get_cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Factory method to add a named solver to the portfolio
+    ///
+    /// The solver's own cancellation token is replaced by the one shared by the portfolio
+    pub fn contender<M>(mut self, name: &str, solver: FixedPointSolver<P, M>) -> Self
+    where
+        M: Mixer<P> + Send + 'a,
+    {
+        let name = name.to_string();
+        self.contenders.push(Box::new(move |mut op, token| {
+            let mut solver = solver.cancellation_token(token.clone());
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| solver.run(&mut op)));
+            let state = solver.get_state();
+
+            let (outcome, result) = match outcome {
+                Ok(Ok(result)) => match state.termination_reason {
+                    reason if reason.converged() => (ContenderOutcome::Converged, Some(result)),
+                    TerminationReason::NotTerminated
+                    | TerminationReason::ObserverTerminated
+                    | TerminationReason::Cancelled => (ContenderOutcome::Cancelled, None),
+                    reason => (ContenderOutcome::Stopped(reason), None),
+                },
+                Ok(Err(e)) => (ContenderOutcome::Failed(e.to_string()), None),
+                Err(_) => (ContenderOutcome::Panicked, None),
+            };
+
+            // Only the first contender to converge cancels the token
+            let won = result.is_some() && token.cancel_first();
+
+            ContenderRun {
+                report: ContenderReport {
+                    name,
+                    iterations: state.iter,
+                    cost: state.cost.cast_f64(),
+                    best_cost: state.best_cost.cast_f64(),
+                    outcome,
+                },
+                result,
+                won,
+            }
+        }));
+        self
+    }
+
+    /// Run all contenders concurrently on clones of the problem
+    pub fn run(self, op: &P) -> PortfolioResult<P> {
+        let runs: Vec<ContenderRun<P>> = thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .contenders
+                .into_iter()
+                .map(|contender| {
+                    let op = op.clone();
+                    let token = &self.cancellation;
+                    scope.spawn(move || contender(op, token))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .expect("contender panics are caught in the thread")
+                })
+                .collect()
+        });
+
+        let mut winner = None;
+        let mut result = None;
+        let mut reports = vec![];
+        for (i, run) in runs.into_iter().enumerate() {
+            if run.won {
+                winner = Some(i);
+                result = run.result;
+            }
+            reports.push(run.report);
+        }
+
+        PortfolioResult {
+            winner,
+            result,
+            reports,
+        }
+    }
+}
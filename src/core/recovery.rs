@@ -0,0 +1,79 @@
+/*!
+Recovery from divergence
+
+When the iteration produces NaN values, or its cost grows far beyond the best found, the solver can
+roll back to the best iterate, discard the mixer's history and continue, optionally damping the
+mixer. Every recovery is recorded in the result.
+*/
+
+use crate::core::FPFloat;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Configuration for recovering from divergence during a run
+pub struct Recovery<F> {
+    max_recoveries: u64,
+    residual_growth: Option<F>,
+    damping: Option<F>,
+}
+
+impl<F: FPFloat> Recovery<F> {
+    /// Generate a configuration allowing the given number of recoveries from NaN values
+    pub fn new(max_recoveries: u64) -> Self {
+        Recovery {
+            max_recoveries,
+            residual_growth: None,
+            damping: None,
+        }
+    }
+
+    /// Factory method to also recover when the cost exceeds the best cost by the given factor
+    pub fn residual_growth(mut self, factor: F) -> Self {
+        self.residual_growth = Some(factor);
+        self
+    }
+
+    /// Factory method to scale the mixing parameter of the mixer by the factor on each recovery
+    pub fn damping(mut self, factor: F) -> Self {
+        self.damping = Some(factor);
+        self
+    }
+
+    /// Returns the maximum number of recoveries in a run
+    pub fn get_max_recoveries(&self) -> u64 {
+        self.max_recoveries
+    }
+
+    /// Returns the factor by which the mixing parameter is scaled on each recovery
+    pub fn get_damping(&self) -> Option<F> {
+        self.damping
+    }
+
+    /// Whether the cost has grown enough beyond the best cost to recover
+    pub(crate) fn has_diverged(&self, cost: F, best_cost: F) -> bool {
+        self.residual_growth
+            .is_some_and(|factor| cost.is_nan() || cost > factor * best_cost)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+/// What triggered a recovery
+pub enum RecoveryCause {
+    /// The mixer produced NaN values
+    NumericalDivergence,
+    /// The cost grew beyond the configured factor of the best cost
+    ResidualGrowth,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Record of a recovery during a run
+pub struct RecoveryEvent {
+    /// Iteration at which the divergence was detected
+    pub iteration: u64,
+    /// Iteration the restored best parameter was reached at
+    pub restored_iteration: u64,
+    /// What triggered the recovery
+    pub cause: RecoveryCause,
+    /// Cost which triggered the recovery, NaN if the mixer produced NaN values
+    pub cost: f64,
+}
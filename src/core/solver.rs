@@ -1,11 +1,16 @@
-use crate::core::{FPIntof64, FixedPointError, FixedPointProblem, IterData, Mixer, State};
+use crate::core::{
+    read_checkpoint, CancellationToken, Checkpoint, CheckpointFormat, Criterion, FPIntof64, FPNorm,
+    FPSub, FixedPointError, FixedPointProblem, IterData, Mixer, Observer, ObserverAction, Recovery,
+    RecoveryCause, RecoveryEvent, SolverError, State, TerminationReason,
+};
 use miette::Result;
-use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
 use tracing::{debug, span, warn, Level};
 
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "M: Serialize", deserialize = "M: Deserialize<'de>"))]
 /// Data type for the solver of a fixed point problem
 pub struct FixedPointSolver<P: FixedPointProblem, M> {
     /// method
@@ -16,6 +21,53 @@ pub struct FixedPointSolver<P: FixedPointProblem, M> {
 
     /// state
     state: State<P>,
+
+    /// observers notified of the progress of the iteration
+    #[serde(skip)]
+    observers: Vec<Arc<Mutex<dyn Observer<P> + Send>>>,
+
+    /// termination criteria
+    #[serde(skip)]
+    criterion: Criterion<P>,
+
+    /// token stopping the run from another thread
+    #[serde(skip)]
+    cancellation: CancellationToken,
+
+    /// checkpoint configuration
+    checkpoint: Option<Checkpoint>,
+
+    /// wall time taken by the mixer in each iteration
+    iteration_times: Vec<instant::Duration>,
+
+    /// cost after each iteration, when recorded
+    cost_history: Option<Vec<P::Float>>,
+
+    /// divergence recovery configuration
+    recovery: Option<Recovery<P::Float>>,
+
+    /// recoveries made as yet
+    recoveries: Vec<RecoveryEvent>,
+
+    /// clock of the run in progress
+    #[serde(skip)]
+    session: Option<Session>,
+}
+
+#[derive(Clone, Copy)]
+/// Clock of a run, started by its first step
+struct Session {
+    /// Instant the run started
+    start: instant::Instant,
+    /// Wall time of earlier runs resumed from a checkpoint
+    previous_time: instant::Duration,
+}
+
+impl Session {
+    /// Wall time including that of earlier runs
+    fn elapsed(&self) -> instant::Duration {
+        self.previous_time + self.start.elapsed()
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -24,11 +76,35 @@ pub struct FixedPointResult<P: FixedPointProblem> {
     /// Result
     param: P::Param,
 
+    /// Best parameter found
+    best_param: P::Param,
+
     /// Cost
     cost: P::Float,
 
-    /// Terminated
-    terminated: bool,
+    /// Cost of the best parameter
+    best_cost: P::Float,
+
+    /// Iterations taken
+    iterations: u64,
+
+    /// Evaluations of the update made
+    evaluations: u64,
+
+    /// Reason the iteration stopped
+    termination_reason: TerminationReason,
+
+    /// Total wall time
+    time: instant::Duration,
+
+    /// Wall time taken by the mixer in each iteration
+    iteration_times: Vec<instant::Duration>,
+
+    /// Cost after each iteration, when recorded
+    cost_history: Option<Vec<P::Float>>,
+
+    /// Recoveries from divergence made during the run
+    recoveries: Vec<RecoveryEvent>,
 }
 
 impl<P: FixedPointProblem> FixedPointResult<P> {
@@ -36,11 +112,67 @@ impl<P: FixedPointProblem> FixedPointResult<P> {
     pub fn get_param(&self) -> P::Param {
         self.param.clone()
     }
+
+    /// Return the parameter with the lowest cost found during the run
+    pub fn get_best_param(&self) -> P::Param {
+        self.best_param.clone()
+    }
+
+    /// Return the cost of the final parameter
+    pub fn get_cost(&self) -> P::Float {
+        self.cost
+    }
+
+    /// Return the cost of the best parameter
+    pub fn get_best_cost(&self) -> P::Float {
+        self.best_cost
+    }
+
+    /// Return the number of iterations taken, including any before a resumed checkpoint
+    pub fn get_iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// Return the number of evaluations of the update made
+    pub fn get_evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Return the reason the iteration stopped
+    pub fn get_termination_reason(&self) -> TerminationReason {
+        self.termination_reason
+    }
+
+    /// Whether the iteration stopped on a tolerance rather than a limit
+    pub fn converged(&self) -> bool {
+        self.termination_reason.converged()
+    }
+
+    /// Return the total wall time of the run
+    pub fn get_time(&self) -> instant::Duration {
+        self.time
+    }
+
+    /// Return the wall time taken by the mixer in each iteration
+    pub fn get_iteration_times(&self) -> &[instant::Duration] {
+        &self.iteration_times
+    }
+
+    /// Return the cost after each iteration, if the solver was asked to record it
+    pub fn get_cost_history(&self) -> Option<&[P::Float]> {
+        self.cost_history.as_deref()
+    }
+
+    /// Return the recoveries from divergence made during the run
+    pub fn get_recoveries(&self) -> &[RecoveryEvent] {
+        &self.recoveries
+    }
 }
 
 impl<P, M> FixedPointSolver<P, M>
 where
     P: FixedPointProblem,
+    P::Param: FPSub<P::Param, P::Param> + FPNorm<P::Float>,
     P::Float: FPIntof64,
     M: Mixer<P>,
 {
@@ -50,75 +182,444 @@ where
             mixer,
             data: initial_parameter.clone(),
             state: State::new(initial_parameter),
+            observers: vec![],
+            criterion: Criterion::default(),
+            cancellation: CancellationToken::new(),
+            checkpoint: None,
+            iteration_times: vec![],
+            cost_history: None,
+            recovery: None,
+            recoveries: vec![],
+            session: None,
         }
     }
 
+    /// Resume a solver from a checkpoint written by an earlier run
+    ///
+    /// The resumed solver continues writing checkpoints with the stored configuration. Observers
+    /// and termination criteria are not stored, and must be set again. A checkpoint written on
+    /// the iteration an observer stopped the run holds its termination reason, which is cleared so
+    /// the resumed run continues
+    pub fn from_checkpoint<T: AsRef<Path>>(
+        path: T,
+        format: CheckpointFormat,
+    ) -> Result<Self, FixedPointError>
+    where
+        M: DeserializeOwned,
+    {
+        let mut solver: Self = read_checkpoint(path.as_ref(), format)?;
+        solver
+            .state
+            .termination_reason(TerminationReason::NotTerminated);
+        Ok(solver)
+    }
+
+    /// Factory method to write checkpoints during the run
+    pub fn checkpoint(mut self, checkpoint: Checkpoint) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Factory method to set the termination criteria
+    ///
+    /// Defaults to an absolute residual of 1e-6 or a thousand iterations, whichever comes first
+    pub fn criterion(mut self, criterion: Criterion<P>) -> Self {
+        self.criterion = criterion;
+        self
+    }
+
+    /// Factory method to stop the run when the given token is cancelled
+    ///
+    /// Sharing one token between several solvers stops them all together
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Returns a handle which stops the run after the current iteration when cancelled
+    ///
+    /// A cancelled token stays cancelled, so a solver whose token was cancelled stops before its
+    /// first iteration when run again
+    pub fn get_cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Factory method to register an observer, which is notified after every iteration
+    ///
+    /// Several observers may be registered, and they are notified in the order they were added
+    pub fn add_observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observer<P> + Send + 'static,
+    {
+        self.observers.push(Arc::new(Mutex::new(observer)));
+        self
+    }
+
+    /// Factory method to record the cost after every iteration in the result
+    pub fn record_cost_history(mut self) -> Self {
+        self.cost_history = Some(vec![]);
+        self
+    }
+
+    /// Factory method to recover from divergence by rolling back to the best iterate
+    pub fn recovery(mut self, recovery: Recovery<P::Float>) -> Self {
+        self.recovery = Some(recovery);
+        self
+    }
+
+    /// Returns a reference to the mixer driving the solver
+    pub fn get_mixer(&self) -> &M {
+        &self.mixer
+    }
+
+    /// Returns a reference to the current state of the solver
+    pub fn get_state(&self) -> &State<P> {
+        &self.state
+    }
+
     /// Run the fixed point solver
-    pub fn run(&mut self, op: &mut P) -> Result<FixedPointResult<P>, FixedPointError> {
+    ///
+    /// Cancelling the solver's token from another thread stops the iteration after the current
+    /// step, returning a result with the `Cancelled` termination reason. Errors carry the state
+    /// of the solver when they occurred, including the best parameter found
+    pub fn run(&mut self, op: &mut P) -> Result<FixedPointResult<P>, SolverError<P>> {
         let span = span!(Level::TRACE, "starting fixed point solver...");
         let _enter = span.enter();
-        let running = Arc::new(AtomicBool::new(true));
 
-        while running.load(Ordering::SeqCst) {
-            if !self.state.terminated() {
-                self.state
-                    .termination_reason(self.mixer.terminate(&self.state).unwrap())
+        loop {
+            match self.step(op) {
+                Ok(Some(_)) => (),
+                Ok(None) => return Ok(self.generate_result()),
+                Err(error) => return Err(SolverError::new(error, self.state.clone())),
             }
+        }
+    }
 
-            if self.state.terminated() {
-                break;
-            }
+    /// Take a single iteration, returning the mixer's output or `None` once terminated
+    ///
+    /// The first step of a run notifies the observers that it has started, and the step which
+    /// terminates or fails notifies them that it has finished, so a later step starts a new run.
+    /// The problem may be modified between steps
+    pub fn step(&mut self, op: &mut P) -> Result<Option<IterData<P>>, FixedPointError> {
+        let session = match self.session {
+            Some(session) => session,
+            None => self.begin(op),
+        };
+        let outcome = self.advance(op, session);
+        if !matches!(outcome, Ok(Some(_))) {
+            self.finish(session, outcome.as_ref().err());
+        }
+        outcome
+    }
 
-            let output = self.mixer.next_iter(op, &self.state).unwrap();
-            self.update(&output);
-            debug!(
+    /// Returns an iterator taking one step per item, yielding the state after each iteration
+    ///
+    /// The iterator ends once the solver terminates, or after yielding an error
+    pub fn steps<'a>(&'a mut self, op: &'a mut P) -> Steps<'a, P, M> {
+        Steps {
+            solver: self,
+            op,
+            finished: false,
+        }
+    }
+
+    /// Notifies the observers and starts the clock at the beginning of a run
+    fn begin(&mut self, op: &mut P) -> Session {
+        for observer in &self.observers {
+            observer
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .observe_init(M::NAME, &self.state);
+        }
+        if let Some(checkpoint) = self.checkpoint.as_mut() {
+            checkpoint.start();
+        }
+        op.set_cancellation_token(self.cancellation.clone());
+
+        // Time from earlier runs resumed from a checkpoint is carried over
+        let session = Session {
+            start: instant::Instant::now(),
+            previous_time: self.state.time.unwrap_or_default(),
+        };
+        self.session = Some(session);
+        session
+    }
+
+    /// Notifies the observers at the end of a run, whether it terminated or failed
+    fn finish(&mut self, session: Session, error: Option<&FixedPointError>) {
+        self.session = None;
+        self.state.time = Some(session.elapsed());
+        for observer in &self.observers {
+            observer
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .observe_final(&self.state);
+        }
+
+        match error {
+            None => debug!(
+                iterations = self.state.iter,
+                cost = self.state.cost.cast_f64(),
+                reason = ?self.state.termination_reason,
+                "Fixed point iteration finished"
+            ),
+            Some(error) => warn!(
                 iteration = self.state.iter,
-                cost = self.state.cost.cast_f64()
-            );
+                best_cost = self.state.best_cost.cast_f64(),
+                "Fixed point iteration failed: {}",
+                error
+            ),
         }
+    }
 
-        // See if we hit the maximum iteration number or not
-        match self.state.iter < self.state.max_iters {
-            true => {
+    /// Checks for termination and otherwise takes an iteration
+    fn advance(
+        &mut self,
+        op: &mut P,
+        session: Session,
+    ) -> Result<Option<IterData<P>>, FixedPointError> {
+        if !self.state.terminated() && self.cancellation.is_cancelled() {
+            debug!(iteration = self.state.iter, "Solver cancelled");
+            self.state.termination_reason(TerminationReason::Cancelled);
+        }
+
+        if !self.state.terminated() {
+            let reason = match self.mixer.terminate(&self.state) {
+                Ok(reason) => reason,
+                Err(e) => {
+                    return Err(FixedPointError::TerminationCheckFailed {
+                        source: e.into(),
+                        iteration: self.state.iter,
+                    })
+                }
+            };
+            self.state.termination_reason(reason);
+        }
+
+        if !self.state.terminated() {
+            self.state.time = Some(session.elapsed());
+            let reason = self.criterion.check(&self.state);
+            if reason.terminated() {
                 debug!(
-                    iterations = self.state.iter,
-                    cost = self.state.cost.cast_f64(),
-                    "Fixed point iteration converged"
+                    iteration = self.state.iter,
+                    reason = ?reason,
+                    "Termination criterion met"
                 );
-                Ok(self.generate_result())
             }
-            false => {
-                warn!("Failed to reach required tolerance");
-                Err(FixedPointError::TooManyIterations(
-                    self.state.cost.cast_f64(),
-                ))
+            self.state.termination_reason(reason);
+        }
+
+        if self.state.terminated() {
+            return Ok(None);
+        }
+
+        let iter_start = instant::Instant::now();
+        let output = match self.mixer.next_iter(op, &self.state) {
+            Ok(output) => output,
+            // An update abandoned on cancellation is not a failure
+            Err(_) if self.cancellation.is_cancelled() => {
+                debug!(iteration = self.state.iter, "Update cancelled");
+                self.state.termination_reason(TerminationReason::Cancelled);
+                return Ok(None);
             }
+            Err(e @ FixedPointError::NumericalDivergence { .. }) => {
+                match self.recover(RecoveryCause::NumericalDivergence) {
+                    true => return self.advance(op, session),
+                    false => return Err(e),
+                }
+            }
+            Err(e) => return Err(e),
+        };
+        let iter_time = iter_start.elapsed();
+        self.update(&output)?;
+        self.state.iter_time = Some(iter_time);
+        self.state.time = Some(session.elapsed());
+        self.iteration_times.push(iter_time);
+        debug!(
+            iteration = self.state.iter,
+            cost = self.state.cost.cast_f64(),
+            time = ?iter_time
+        );
+
+        // Every observer sees the iteration, even if an earlier one asks to stop
+        let mut terminate = false;
+        for observer in &self.observers {
+            let action = observer
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .observe_iter(&self.state, &output);
+            terminate |= action == ObserverAction::Terminate;
+        }
+        if terminate {
+            debug!(
+                iteration = self.state.iter,
+                "Observer requested termination"
+            );
+            self.state
+                .termination_reason(TerminationReason::ObserverTerminated);
         }
+
+        if self
+            .recovery
+            .as_ref()
+            .is_some_and(|r| r.has_diverged(self.state.cost, self.state.best_cost))
+        {
+            self.recover(RecoveryCause::ResidualGrowth);
+        }
+
+        if self
+            .checkpoint
+            .as_ref()
+            .is_some_and(|c| c.is_due(self.state.iter))
+        {
+            let mut checkpoint = self.checkpoint.take().unwrap();
+            let written = checkpoint.write(&*self);
+            self.checkpoint = Some(checkpoint);
+            written?;
+            debug!(iteration = self.state.iter, "Wrote checkpoint");
+        }
+
+        Ok(Some(output))
+    }
+
+    /// Rolls back to the best iterate and restarts the mixer, unless no recoveries remain
+    fn recover(&mut self, cause: RecoveryCause) -> bool {
+        let damping = match self.recovery.as_ref() {
+            Some(recovery) if (self.recoveries.len() as u64) < recovery.get_max_recoveries() => {
+                recovery.get_damping()
+            }
+            _ => return false,
+        };
+        let cost = match cause {
+            RecoveryCause::NumericalDivergence => f64::NAN,
+            RecoveryCause::ResidualGrowth => self.state.cost.cast_f64(),
+        };
+        warn!(
+            iteration = self.state.iter,
+            restored_iteration = self.state.last_best_iter,
+            cause = ?cause,
+            "Recovering from divergence"
+        );
+        self.recoveries.push(RecoveryEvent {
+            iteration: self.state.iter,
+            restored_iteration: self.state.last_best_iter,
+            cause,
+            cost,
+        });
+
+        self.state.param = self.state.best_param.clone();
+        self.state.prev_param = self.state.best_param.clone();
+        self.state.cost = self.state.best_cost;
+        self.state.recovered = true;
+        self.mixer.reset();
+        if let Some(factor) = damping {
+            self.mixer.dampen(factor);
+        }
+        true
     }
 
     /// Generates the result from a converged or non-converged solution
     fn generate_result(&self) -> FixedPointResult<P> {
         FixedPointResult {
             param: self.state.get_param(),
+            best_param: self.state.best_param.clone(),
             cost: self.state.cost,
-            terminated: self.state.terminated(),
+            best_cost: self.state.best_cost,
+            iterations: self.state.iter,
+            evaluations: self.state.evaluations,
+            termination_reason: self.state.termination_reason,
+            time: self.state.time.unwrap_or_default(),
+            iteration_times: self.iteration_times.clone(),
+            cost_history: self.cost_history.clone(),
+            recoveries: self.recoveries.clone(),
         }
     }
 
     /// Updates the State struct based on a single iteration's output
-    fn update(&mut self, output: &IterData<P>) {
-        self.state.prev_param = self.state.param.clone();
-        self.state.param = output.get_param().unwrap();
+    fn update(&mut self, output: &IterData<P>) -> Result<(), FixedPointError> {
+        let (param, cost) = match (output.get_param(), output.get_cost()) {
+            (Some(param), Some(cost)) => (param, cost),
+            _ => return Err(FixedPointError::UnexpectedOutcome),
+        };
+        self.state.prev_param = std::mem::replace(&mut self.state.param, param);
+        self.state.recovered = false;
         self.state.prev_cost = self.state.cost;
-        self.state.cost = output.get_cost().unwrap();
+        self.state.cost = cost;
+        if let Some(history) = self.cost_history.as_mut() {
+            history.push(cost);
+        }
+        self.state.evaluations += output.get_evaluations().unwrap_or(1);
+        if self.state.iter == 0 {
+            self.state.initial_cost = self.state.cost;
+        }
 
         if self.state.cost < self.state.best_cost {
             self.state.prev_best_cost = self.state.best_cost;
             self.state.prev_best_param = self.state.best_param.clone();
             self.state.best_cost = self.state.cost;
             self.state.best_param = self.state.param.clone();
+            self.state.last_best_iter = self.state.iter + 1;
         }
 
         self.state.iter += 1;
+        Ok(())
+    }
+}
+
+/// Iterator taking one step of a solver per item, see `FixedPointSolver::steps`
+pub struct Steps<'a, P: FixedPointProblem, M> {
+    solver: &'a mut FixedPointSolver<P, M>,
+    op: &'a mut P,
+    finished: bool,
+}
+
+impl<P, M> Steps<'_, P, M>
+where
+    P: FixedPointProblem,
+{
+    /// Returns a reference to the solver being stepped
+    pub fn get_solver(&self) -> &FixedPointSolver<P, M> {
+        self.solver
+    }
+
+    /// Returns a mutable reference to the problem, to modify it between steps
+    pub fn get_problem_mut(&mut self) -> &mut P {
+        self.op
+    }
+}
+
+impl<P, M> Iterator for Steps<'_, P, M>
+where
+    P: FixedPointProblem,
+    P::Param: FPSub<P::Param, P::Param> + FPNorm<P::Float>,
+    P::Float: FPIntof64,
+    M: Mixer<P>,
+{
+    type Item = Result<State<P>, FixedPointError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.solver.step(self.op) {
+            Ok(Some(_)) => Some(Ok(self.solver.state.clone())),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(error) => {
+                self.finished = true;
+                Some(Err(error))
+            }
+        }
     }
 }
+
+impl<P, M> std::iter::FusedIterator for Steps<'_, P, M>
+where
+    P: FixedPointProblem,
+    P::Param: FPSub<P::Param, P::Param> + FPNorm<P::Float>,
+    P::Float: FPIntof64,
+    M: Mixer<P>,
+{
+}
@@ -4,7 +4,7 @@ use num::traits::float::Float;
 use paste::item;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 /// Struct to hold the iteration state
 pub struct State<O: FixedPointProblem> {
     /// Current parameter vector
@@ -23,16 +23,46 @@ pub struct State<O: FixedPointProblem> {
     pub best_cost: O::Float,
     /// Previous best cost
     pub prev_best_cost: O::Float,
+    /// Cost reported by the first iteration, the reference for relative tolerances
+    pub initial_cost: O::Float,
     /// Current iteration
     pub iter: u64,
+    /// Number of evaluations of the update made as yet
+    pub evaluations: u64,
     /// Iteration number of last best cost
     pub last_best_iter: u64,
-    /// Maximum number of iterations
-    pub max_iters: u64,
-    /// Time required as yet
+    /// Wall time elapsed as yet, including any earlier runs resumed from a checkpoint
     pub time: Option<instant::Duration>,
+    /// Wall time taken by the mixer in the latest iteration
+    pub iter_time: Option<instant::Duration>,
     /// Termination reason
     pub termination_reason: TerminationReason,
+    /// Whether the parameter was restored to the best iterate since the last iteration
+    pub recovered: bool,
+}
+
+/// Implemented by hand as the derive would require the problem itself to be Clone
+impl<O: FixedPointProblem> Clone for State<O> {
+    fn clone(&self) -> Self {
+        State {
+            param: self.param.clone(),
+            prev_param: self.prev_param.clone(),
+            best_param: self.best_param.clone(),
+            prev_best_param: self.prev_best_param.clone(),
+            cost: self.cost,
+            prev_cost: self.prev_cost,
+            best_cost: self.best_cost,
+            prev_best_cost: self.prev_best_cost,
+            initial_cost: self.initial_cost,
+            iter: self.iter,
+            evaluations: self.evaluations,
+            last_best_iter: self.last_best_iter,
+            time: self.time,
+            iter_time: self.iter_time,
+            termination_reason: self.termination_reason,
+            recovered: self.recovered,
+        }
+    }
 }
 
 // macro_rules! setter {
@@ -79,21 +109,20 @@ impl<O: FixedPointProblem> State<O> {
             prev_cost: O::Float::infinity(),
             best_cost: O::Float::infinity(),
             prev_best_cost: O::Float::infinity(),
+            initial_cost: O::Float::infinity(),
             iter: 0,
+            evaluations: 0,
             last_best_iter: 0,
-            max_iters: std::u64::MAX,
             time: Some(instant::Duration::new(0, 0)),
+            iter_time: None,
             termination_reason: TerminationReason::NotTerminated,
+            recovered: false,
         }
     }
 
     /// Verify whether the solution has terminated
     pub fn terminated(&self) -> bool {
-        match self.termination_reason {
-            TerminationReason::NotTerminated => false,
-            TerminationReason::ToleranceBeaten => true,
-            TerminationReason::HitMaxIterations => true,
-        }
+        self.termination_reason.terminated()
     }
 
     /// Set the termination reason
@@ -104,15 +133,48 @@ impl<O: FixedPointProblem> State<O> {
     getter!(param, O::Param, "Returns current parameter vector");
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 /// Enum for termination conditions
 pub enum TerminationReason {
     /// The iteration is in progress
     NotTerminated,
     /// The iteration has converged
     ToleranceBeaten,
+    /// The cost has fallen below the tolerance relative to the first cost
+    RelativeToleranceBeaten,
+    /// The step has fallen below the tolerance
+    StepToleranceBeaten,
     /// The maximum iterations have been reached
     HitMaxIterations,
+    /// The maximum evaluations of the update have been reached
+    HitMaxEvaluations,
+    /// An observer requested that the iteration stop
+    ObserverTerminated,
+    /// Another iteration would exceed the time limit
+    TimeLimitReached,
+    /// The best cost has not improved within the stagnation window
+    Stagnated,
+    /// A user supplied criterion was met
+    UserCriterion,
+    /// The solver's cancellation token was cancelled
+    Cancelled,
+}
+
+impl TerminationReason {
+    /// Whether the reason stops the iteration
+    pub fn terminated(&self) -> bool {
+        !matches!(self, TerminationReason::NotTerminated)
+    }
+
+    /// Whether the reason is one of the residual or step tolerances
+    pub fn converged(&self) -> bool {
+        matches!(
+            self,
+            TerminationReason::ToleranceBeaten
+                | TerminationReason::RelativeToleranceBeaten
+                | TerminationReason::StepToleranceBeaten
+        )
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -122,6 +184,10 @@ pub struct IterData<P: FixedPointProblem> {
     param: Option<P::Param>,
     /// The associated cost ||f(x) - x||
     cost: Option<P::Float>,
+    /// The number of evaluations of the update made in the step
+    evaluations: Option<u64>,
+    /// The update f(x) at the parameter, if the mixer evaluated it
+    update: Option<P::Param>,
 }
 
 impl<P: FixedPointProblem> IterData<P> {
@@ -130,6 +196,8 @@ impl<P: FixedPointProblem> IterData<P> {
         IterData {
             param: None,
             cost: None,
+            evaluations: None,
+            update: None,
         }
     }
 
@@ -145,6 +213,28 @@ impl<P: FixedPointProblem> IterData<P> {
         self
     }
 
+    /// Factory method to set 'evaluations' field
+    pub fn evaluations(mut self, evaluations: u64) -> Self {
+        self.evaluations = Some(evaluations);
+        self
+    }
+
+    /// Factory method to set 'update' field
+    pub fn update(mut self, update: P::Param) -> Self {
+        self.update = Some(update);
+        self
+    }
+
     ogetter!(param, P::Param, "Returns current parameter vector");
+    ogetter!(
+        evaluations,
+        u64,
+        "Returns the evaluations of the update made in the step"
+    );
     ogetter!(cost, P::Float, "Returns current cost");
+    ogetter!(
+        update,
+        P::Param,
+        "Returns the update evaluated at the parameter, saving wrapping mixers an evaluation"
+    );
 }
@@ -13,6 +13,12 @@ pub mod solvers;
 /// Everything our users need
 pub mod prelude;
 
+/// Fixed point maps from operator splittings
+pub mod splitting;
+
+/// Tuning of mixer parameters
+pub mod tuning;
+
 #[cfg(test)]
 /// Top level testing
 mod testing;
@@ -13,12 +13,10 @@ use tracing::debug;
 /// Type 1 Anderson Mixer with stabilisation
 pub struct Type1AndersonMixer<F, P: FixedPointProblem> {
     dim: usize,
-    tol: F,
     regularisation: F,
     safeguard_factor: F,
     iter: u64,
     m: u64,
-    max_iter: u64,
     beta: F,
     epsilon: F,
     tau: F,
@@ -71,7 +69,7 @@ where
         + FPStack<P::Param>,
 {
     fn default() -> Self {
-        Type1AndersonMixer::new(10, F::from_f64(1e-6).unwrap(), 1000)
+        Type1AndersonMixer::new(10)
     }
 }
 
@@ -96,15 +94,13 @@ where
         + FPStack<P::Param>,
 {
     /// Generate a new Anderson Mixer with default parameters
-    pub fn new(dimension: usize, tolerance: F, max_iter: u64) -> Self {
+    pub fn new(dimension: usize) -> Self {
         Type1AndersonMixer {
             dim: dimension,
-            tol: tolerance,
             regularisation: F::from_f64(1.).unwrap(),
             safeguard_factor: F::from_f64(1e6).unwrap(),
             iter: 0,
             m: 0,
-            max_iter,
             beta: F::from_f64(1.).unwrap(),
             memory: 5,
             theta_bar: F::from_f64(1e-2).unwrap(),
@@ -162,7 +158,13 @@ where
     fn init(&mut self, op: &mut P, state: &State<P>) -> Result<(), FixedPointError> {
         self.fx0 = match op.update(&state.get_param()) {
             Ok(x) => x,
-            Err(_) => return Err(FixedPointError::UpdateFailed),
+            Err(e) => {
+                return Err(FixedPointError::update_failed(
+                    e,
+                    state.iter,
+                    state.param.norm(),
+                ))
+            }
         };
         self.x0 = state.param.clone();
         self.g0 = self.x0.sub(&self.fx0);
@@ -182,8 +184,8 @@ where
         }
     }
 
-    /// Safeguarding step
-    fn safeguard(&mut self, op: &mut P) -> Result<(), FixedPointError> {
+    /// Safeguarding step at the solver's iteration, returning the number of evaluations made
+    fn safeguard(&mut self, op: &mut P, iter: u64) -> Result<u64, FixedPointError> {
         let ubar0 = self.g0.norm();
         let factor = self.ubar
             * self.safeguard_factor
@@ -195,6 +197,7 @@ where
             self.n_anderson += 1;
             self.x0 = self.x1.clone();
             self.fx0 = self.fx1.clone();
+            Ok(0)
         } else {
             debug!(iteration = self.iter, "Taking Linear Step");
             self.x1 = self
@@ -204,10 +207,10 @@ where
             self.x0 = self.x1.clone();
             self.fx0 = match op.update(&self.x0) {
                 Ok(x) => x,
-                Err(_) => return Err(FixedPointError::UpdateFailed),
-            }
+                Err(e) => return Err(FixedPointError::update_failed(e, iter, self.x0.norm())),
+            };
+            Ok(1)
         }
-        Ok(())
     }
 
     /// Powell regularisation step
@@ -306,11 +309,13 @@ where
     const NAME: &'static str = "Type-I Anderson Mixing";
 
     fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError> {
+        let mut evaluations = 1;
         if self.iter == 0 {
             match self.init(op, state) {
                 Ok(_) => (),
                 Err(e) => return Err(e),
             }
+            evaluations += 1;
         }
 
         self.m += 1;
@@ -323,13 +328,19 @@ where
         self.s0 = self.x1.sub(&self.x0);
         self.fx1 = match op.update(&self.x1) {
             Ok(x) => x,
-            Err(_) => return Err(FixedPointError::UpdateFailed),
+            Err(e) => {
+                return Err(FixedPointError::update_failed(
+                    e,
+                    state.iter,
+                    self.x1.norm(),
+                ))
+            }
         };
         self.g1 = self.x1.sub(&self.fx1);
         self.y0 = self.g1.sub(&self.g0);
 
-        match self.safeguard(op) {
-            Ok(_) => (),
+        match self.safeguard(op, state.iter) {
+            Ok(n) => evaluations += n,
             Err(e) => return Err(e),
         };
 
@@ -342,21 +353,29 @@ where
         self.iter += 1;
 
         match self.x1.holds_nan() {
-            true => return Err(FixedPointError::NumericalDivergence),
+            true => {
+                return Err(FixedPointError::NumericalDivergence {
+                    iteration: state.iter,
+                })
+            }
             false => (),
         };
 
-        Ok(IterData::new().cost(res.norm()).param(self.x1.clone()))
+        Ok(IterData::new()
+            .cost(res.norm())
+            .param(self.x1.clone())
+            .evaluations(evaluations)
+            .update(self.fx0.clone()))
     }
 
-    fn terminate(&mut self, state: &State<P>) -> Result<TerminationReason> {
-        let condition = if state.cost < self.tol {
-            TerminationReason::ToleranceBeaten
-        } else if state.iter > self.max_iter {
-            TerminationReason::HitMaxIterations
-        } else {
-            TerminationReason::NotTerminated
-        };
-        Ok(condition)
+    fn dampen(&mut self, factor: F) {
+        self.beta = self.beta * factor;
+    }
+
+    fn reset(&mut self) {
+        self.iter = 0;
+        self.s_hat_memory = P::Square::zeros(0);
+        self.hv1 = P::Square::zeros(0);
+        self.hv2 = P::Square::zeros(0);
     }
 }
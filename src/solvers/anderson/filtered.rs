@@ -0,0 +1,209 @@
+/*!
+Filtered Anderson Mixer
+
+Type-II Anderson acceleration in which the least-squares problem is solved through a QR
+factorisation of the residual-difference history, and columns which make the factorisation
+ill-conditioned are removed from the history rather than resetting the whole memory.
+
+A column is dropped when the part of it orthogonal to the newer columns is smaller than the
+filtering tolerance times its own norm, so the newest information is always retained.
+
+Reference: https://doi.org/10.1137/20M1342212 (Pollock & Rebholz, Filtering for Anderson acceleration)
+*/
+
+use crate::prelude::*;
+use crate::solvers::history::SecantHistory;
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tracing::debug;
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Type 2 Anderson Mixer with filtering of ill-conditioned history columns
+pub struct FilteredAndersonMixer<F, P: FixedPointProblem> {
+    beta: F,
+    memory: usize,
+    filter_tolerance: F,
+    record_length: usize,
+
+    /// Internal data
+    #[serde(bound(
+        serialize = "P::Param: Serialize",
+        deserialize = "P::Param: Deserialize<'de>"
+    ))]
+    history: SecantHistory<P>,
+    dropped_columns: VecDeque<Vec<usize>>,
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> std::default::Default
+    for FilteredAndersonMixer<F, P>
+{
+    fn default() -> Self {
+        FilteredAndersonMixer::new()
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> FilteredAndersonMixer<F, P> {
+    /// Generate a new filtered Anderson mixer with default parameters
+    pub fn new() -> Self {
+        FilteredAndersonMixer {
+            beta: F::from_f64(1.).unwrap(),
+            memory: 5,
+            filter_tolerance: F::from_f64(1e-4).unwrap(),
+            record_length: 100,
+            history: SecantHistory::new(),
+            dropped_columns: VecDeque::new(),
+        }
+    }
+
+    /// Factory method to set the relaxation beta
+    pub fn beta(mut self, beta: F) -> Self {
+        self.beta = beta;
+        self
+    }
+
+    /// Factory method to set the memory size for the mixer
+    pub fn memory(mut self, memory: usize) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// Factory method to set the filtering tolerance
+    pub fn filter_tolerance(mut self, filter_tolerance: F) -> Self {
+        self.filter_tolerance = filter_tolerance;
+        self
+    }
+
+    /// Factory method to set the number of recent iterations whose dropped columns are kept
+    pub fn record_length(mut self, record_length: usize) -> Self {
+        self.record_length = record_length;
+        self
+    }
+
+    /// Returns the history columns dropped at each of the most recent iterations, oldest first
+    ///
+    /// Indices refer to the history before filtering, ordered from oldest to newest
+    pub fn get_dropped_columns(&self) -> &VecDeque<Vec<usize>> {
+        &self.dropped_columns
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> FilteredAndersonMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPDiv<P::Float, P::Param>
+        + FPNorm<P::Float>
+        + FPDot<P::Param, P::Float>,
+{
+    /// Filters the history and factorises the remaining residual differences
+    ///
+    /// Columns are orthogonalised from newest to oldest by modified Gram-Schmidt. The returned
+    /// vectors hold the orthonormal columns, the upper-triangular factor and the history index
+    /// of each retained column, all ordered from newest to oldest.
+    fn filter(&mut self, iter: u64) -> (Vec<P::Param>, Vec<Vec<F>>, Vec<usize>) {
+        let mut q: Vec<P::Param> = vec![];
+        let mut r: Vec<Vec<F>> = vec![];
+        let mut retained = vec![];
+        let mut dropped = vec![];
+
+        for idx in (0..self.history.len()).rev() {
+            let column = &self.history.get_dg()[idx];
+            let column_norm = column.norm();
+            let mut v = column.clone();
+            let mut r_col = vec![];
+            for qi in q.iter() {
+                let rij = qi.dot(&v);
+                v = v.sub(&qi.mul(&rij));
+                r_col.push(rij);
+            }
+            let rjj = v.norm();
+            if rjj <= self.filter_tolerance * column_norm {
+                dropped.push(idx);
+                continue;
+            }
+            r_col.push(rjj);
+            q.push(v.div(&rjj));
+            r.push(r_col);
+            retained.push(idx);
+        }
+
+        if !dropped.is_empty() {
+            debug!(iteration = iter, dropped = ?dropped, "Filtered Anderson history");
+            // Remove from newest to oldest so the remaining indices stay valid
+            for &idx in dropped.iter() {
+                self.history.remove(idx);
+            }
+            for idx in retained.iter_mut() {
+                *idx -= dropped.iter().filter(|&&d| d < *idx).count();
+            }
+        }
+
+        dropped.reverse();
+        self.dropped_columns.push_back(dropped);
+        if self.dropped_columns.len() > self.record_length {
+            self.dropped_columns.pop_front();
+        }
+
+        (q, r, retained)
+    }
+}
+
+impl<P, F> Mixer<P> for FilteredAndersonMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPDiv<P::Float, P::Param>
+        + FPNorm<P::Float>
+        + FPDot<P::Param, P::Float>
+        + FPHoldsNaN,
+    P: FixedPointProblem<Float = F>,
+    F: FPFloat,
+{
+    const NAME: &'static str = "Filtered Anderson Mixing";
+
+    fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError> {
+        let (g, restart_evaluations) = self.history.start(op, state)?;
+        if self.history.len() > self.memory {
+            self.history.pop_front();
+        }
+
+        let (q, r, retained) = self.filter(state.iter);
+
+        // Solve R gamma = Q^T g by back substitution, columns ordered newest to oldest
+        let qtg: Vec<F> = q.iter().map(|qi| qi.dot(&g)).collect();
+        let mut gamma = vec![F::zero(); q.len()];
+        for j in (0..q.len()).rev() {
+            let mut sum = qtg[j];
+            for (k, gk) in gamma.iter().enumerate().skip(j + 1) {
+                sum = sum - r[k][j] * *gk;
+            }
+            gamma[j] = sum / r[j][j];
+        }
+
+        // Retained columns are ordered newest to oldest, the history oldest to newest
+        let mut coefficients = vec![F::zero(); self.history.len()];
+        for (gj, &idx) in gamma.iter().zip(retained.iter()) {
+            coefficients[idx] = *gj;
+        }
+        let new_x = self
+            .history
+            .extrapolate(&state.param, &g, self.beta, &coefficients);
+        let (update, cost) = self.history.propose(op, state, g, &new_x)?;
+
+        Ok(IterData::new()
+            .cost(cost)
+            .param(new_x)
+            .evaluations(restart_evaluations + 1)
+            .update(update))
+    }
+
+    fn dampen(&mut self, factor: F) {
+        self.beta = self.beta * factor;
+    }
+
+    fn reset(&mut self) {
+        self.history.reset();
+    }
+}
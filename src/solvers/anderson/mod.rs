@@ -3,5 +3,11 @@ Anderson Mixers
 */
 
 pub mod andersontype1;
+pub mod filtered;
+pub mod sketched;
+pub mod tikhonov;
 
 pub use self::andersontype1::*;
+pub use self::filtered::*;
+pub use self::sketched::*;
+pub use self::tikhonov::*;
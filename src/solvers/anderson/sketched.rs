@@ -0,0 +1,273 @@
+/*!
+Randomised Sketched Anderson Mixer
+
+Type-II Anderson acceleration whose least-squares problem is solved on a random sketch of the
+residual history,
+
+gamma = argmin ||S g - S Y gamma||
+
+where S is a sparse sign matrix, a selection of rows of the identity or a Gaussian matrix. The
+sketch is drawn from a seeded generator, so each new history column is compressed as it arrives
+and the inner products are taken in the sketch dimension rather than the problem dimension.
+
+The sparse sign sketch (CountSketch) adds every element, with a random sign, to one random row of
+the sketch. Rows and signs are hashed from the seed and the element index, so applying it is a
+single cheap pass over the vector and nothing proportional to the problem dimension is stored.
+A Gaussian sketch regenerates its dense matrix from the seed on every application, costing
+size × n normal draws, which is more work than the exact inner products it replaces; it is
+offered as the accurate reference among the sketches rather than for speed. `Sketch::Exact`
+takes the inner products in full, giving the unsketched Type-II Anderson method.
+
+Reference: https://doi.org/10.1137/20M1365223 (Martinsson & Tropp, Randomized numerical linear
+algebra)
+*/
+
+use crate::prelude::*;
+use crate::solvers::history::SecantHistory;
+use crate::solvers::linalg;
+use miette::Result;
+use rand::{seq::index, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+/// Random sketch applied to the residual history
+pub enum Sketch {
+    /// No sketch, taking the inner products in the full problem dimension
+    Exact,
+    /// Sparse sign sketch hashing each element to one of the given number of rows
+    SparseSign(usize),
+    /// Selection of the given number of rows, sampled uniformly without replacement
+    Subsample(usize),
+    /// Projection onto the given number of Gaussian random directions, redrawn on each use
+    Gaussian(usize),
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+/// A realised sketch for a problem of known dimension
+struct SketchOperator {
+    dimension: usize,
+    kind: SketchKind,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Size and seed of the hashed or Gaussian matrix, or the indices of the selected rows
+enum SketchKind {
+    Exact,
+    SparseSign { size: usize, seed: u64 },
+    Subsample(Vec<usize>),
+    Gaussian { size: usize, seed: u64 },
+}
+
+/// Mixes the seed and an element index into well-distributed bits (SplitMix64 finaliser)
+fn hash(seed: u64, index: usize) -> u64 {
+    let mut z = seed ^ (index as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl SketchOperator {
+    /// Draws a sketch for a problem of dimension `n`
+    fn draw(sketch: Sketch, n: usize, seed: u64) -> Self {
+        let kind = match sketch {
+            Sketch::Exact => SketchKind::Exact,
+            Sketch::SparseSign(size) => SketchKind::SparseSign {
+                size: size.max(1),
+                seed,
+            },
+            Sketch::Gaussian(size) => SketchKind::Gaussian { size, seed },
+            Sketch::Subsample(size) => {
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                SketchKind::Subsample(index::sample(&mut rng, n, size.min(n)).into_vec())
+            }
+        };
+        SketchOperator { dimension: n, kind }
+    }
+
+    /// Applies the sketch to a vector
+    fn apply<F: FPFloat, T: FPGather<F> + FPFoldValues<F>>(&self, x: &T) -> Vec<F> {
+        match &self.kind {
+            SketchKind::Exact => x.fold_values(vec![], |mut values, value| {
+                values.push(*value);
+                values
+            }),
+            SketchKind::SparseSign { size, seed } => {
+                let (sketched, _) =
+                    x.fold_values((vec![F::zero(); *size], 0), |(mut sketched, i), value| {
+                        let bits = hash(*seed, i);
+                        let row = (bits >> 1) as usize % *size;
+                        sketched[row] = match bits & 1 {
+                            0 => sketched[row] + *value,
+                            _ => sketched[row] - *value,
+                        };
+                        (sketched, i + 1)
+                    });
+                sketched
+            }
+            SketchKind::Gaussian { size, seed } => {
+                // Each element draws its column of the matrix from the same stream every time
+                let mut rng = ChaCha8Rng::seed_from_u64(*seed);
+                x.fold_values(vec![F::zero(); *size], |mut sketched, value| {
+                    for s in sketched.iter_mut() {
+                        let entry = F::from_f64(rng.sample(StandardNormal)).unwrap();
+                        *s = *s + entry * *value;
+                    }
+                    sketched
+                })
+            }
+            SketchKind::Subsample(indices) => x.gather(indices),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Type 2 Anderson Mixer solving its least-squares problem on a random sketch
+pub struct SketchedAndersonMixer<F, P: FixedPointProblem> {
+    beta: F,
+    memory: usize,
+    sketch: Sketch,
+    seed: u64,
+
+    /// Internal data
+    operator: Option<SketchOperator>,
+    #[serde(bound(
+        serialize = "P::Param: Serialize",
+        deserialize = "P::Param: Deserialize<'de>"
+    ))]
+    history: SecantHistory<P>,
+    sketched_history: VecDeque<Vec<F>>,
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> std::default::Default
+    for SketchedAndersonMixer<F, P>
+{
+    fn default() -> Self {
+        SketchedAndersonMixer::new()
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> SketchedAndersonMixer<F, P> {
+    /// Generate a new sketched Anderson mixer sampling twenty rows
+    pub fn new() -> Self {
+        SketchedAndersonMixer {
+            beta: F::from_f64(1.).unwrap(),
+            memory: 5,
+            sketch: Sketch::Subsample(20),
+            seed: 0,
+            operator: None,
+            history: SecantHistory::new(),
+            sketched_history: VecDeque::new(),
+        }
+    }
+
+    /// Factory method to set the relaxation beta
+    pub fn beta(mut self, beta: F) -> Self {
+        self.beta = beta;
+        self
+    }
+
+    /// Factory method to set the memory size for the mixer
+    pub fn memory(mut self, memory: usize) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// Factory method to set the kind and size of the sketch
+    pub fn sketch(mut self, sketch: Sketch) -> Self {
+        self.sketch = sketch;
+        self.operator = None;
+        self
+    }
+
+    /// Factory method to set the seed of the random number generator drawing the sketch
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self.operator = None;
+        self
+    }
+}
+
+impl<P, F> Mixer<P> for SketchedAndersonMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPNorm<P::Float>
+        + FPGather<P::Float>
+        + FPFoldValues<P::Float>
+        + FPDimension
+        + FPHoldsNaN,
+    P: FixedPointProblem<Float = F>,
+    F: FPFloat,
+{
+    const NAME: &'static str = "Sketched Anderson Mixing";
+
+    fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError> {
+        let n = state.param.dimension();
+        if self.operator.as_ref().is_none_or(|s| s.dimension != n) {
+            self.operator = Some(SketchOperator::draw(self.sketch, n, self.seed));
+            self.reset();
+        }
+        let operator = self.operator.as_ref().unwrap();
+
+        let (g, restart_evaluations) = self.history.start(op, state)?;
+        // Clears the sketches if the history restarted
+        self.sketched_history.truncate(self.history.len());
+        if self.history.len() > self.sketched_history.len() {
+            let dg = self.history.get_dg().back().unwrap();
+            self.sketched_history.push_back(operator.apply(dg));
+        }
+        if self.history.len() > self.memory {
+            self.history.pop_front();
+            self.sketched_history.pop_front();
+        }
+
+        let mut gamma = vec![];
+        if !self.sketched_history.is_empty() {
+            let sketched_g = operator.apply(&g);
+            let dot = |a: &[F], b: &[F]| {
+                a.iter()
+                    .zip(b.iter())
+                    .fold(F::zero(), |acc, (x, y)| acc + *x * *y)
+            };
+            let gram: Vec<Vec<F>> = self
+                .sketched_history
+                .iter()
+                .map(|yi| self.sketched_history.iter().map(|yj| dot(yi, yj)).collect())
+                .collect();
+            let rhs: Vec<F> = self
+                .sketched_history
+                .iter()
+                .map(|yi| dot(yi, &sketched_g))
+                .collect();
+
+            // A singular sketched system leaves the relaxed linear step
+            if let Some(solution) = linalg::solve(gram, rhs) {
+                gamma = solution;
+            }
+        }
+
+        let new_x = self
+            .history
+            .extrapolate(&state.param, &g, self.beta, &gamma);
+        let (update, cost) = self.history.propose(op, state, g, &new_x)?;
+
+        Ok(IterData::new()
+            .cost(cost)
+            .param(new_x)
+            .evaluations(restart_evaluations + 1)
+            .update(update))
+    }
+
+    fn dampen(&mut self, factor: F) {
+        self.beta = self.beta * factor;
+    }
+
+    fn reset(&mut self) {
+        self.history.reset();
+        self.sketched_history.clear();
+    }
+}
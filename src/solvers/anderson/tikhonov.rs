@@ -0,0 +1,211 @@
+/*!
+Tikhonov-Regularised Anderson Mixer
+
+Type-II Anderson acceleration whose least-squares problem is regularised,
+
+gamma = argmin ||g - Y gamma||^2 + lambda ||gamma||^2,  (Y^T Y + lambda I) gamma = Y^T g
+
+with the parameter lambda chosen afresh at every iteration, either proportional to the squared
+residual norm or at the corner of the L-curve. The chosen value is emitted as a `debug` event.
+
+Reference: https://doi.org/10.1137/1.9780898718836 (Hansen, Discrete Inverse Problems)
+*/
+
+use crate::prelude::*;
+use crate::solvers::history::SecantHistory;
+use crate::solvers::linalg;
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+/// Rule for choosing the Tikhonov parameter at each iteration
+pub enum RegularisationRule<F> {
+    /// The parameter is the given factor times the squared norm of the current residual
+    ResidualScaled(F),
+    /// The parameter maximises the curvature of the L-curve over a logarithmic grid
+    LCurve,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Type 2 Anderson Mixer with automatically chosen Tikhonov regularisation
+pub struct TikhonovAndersonMixer<F, P: FixedPointProblem> {
+    beta: F,
+    memory: usize,
+    rule: RegularisationRule<F>,
+    regularisation: F,
+
+    /// Internal data
+    #[serde(bound(
+        serialize = "P::Param: Serialize",
+        deserialize = "P::Param: Deserialize<'de>"
+    ))]
+    history: SecantHistory<P>,
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> std::default::Default
+    for TikhonovAndersonMixer<F, P>
+{
+    fn default() -> Self {
+        TikhonovAndersonMixer::new()
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> TikhonovAndersonMixer<F, P> {
+    /// Generate a new regularised Anderson mixer choosing the parameter from the L-curve
+    pub fn new() -> Self {
+        TikhonovAndersonMixer {
+            beta: F::from_f64(1.).unwrap(),
+            memory: 5,
+            rule: RegularisationRule::LCurve,
+            regularisation: F::zero(),
+            history: SecantHistory::new(),
+        }
+    }
+
+    /// Factory method to set the relaxation beta
+    pub fn beta(mut self, beta: F) -> Self {
+        self.beta = beta;
+        self
+    }
+
+    /// Factory method to set the memory size for the mixer
+    pub fn memory(mut self, memory: usize) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// Factory method to set the rule choosing the regularisation parameter
+    pub fn rule(mut self, rule: RegularisationRule<F>) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    /// Returns the regularisation parameter chosen at the last iteration
+    pub fn get_regularisation(&self) -> F {
+        self.regularisation
+    }
+}
+
+/// Solves the regularised normal equations for a given parameter
+fn regularised_solve<F: FPFloat>(gram: &[Vec<F>], rhs: &[F], lambda: F) -> Option<Vec<F>> {
+    let mut matrix = gram.to_vec();
+    for (i, row) in matrix.iter_mut().enumerate() {
+        row[i] = row[i] + lambda;
+    }
+    linalg::solve(matrix, rhs.to_vec())
+}
+
+/// Chooses the parameter at the corner of the L-curve
+///
+/// The curve (log ||g - Y gamma||, log ||gamma||) is sampled on a logarithmic grid of parameters
+/// relative to the mean eigenvalue of the Gram matrix, and the sample with the largest
+/// curvature, estimated from the circle through it and its neighbours, is returned.
+fn l_curve<F: FPFloat>(gram: &[Vec<F>], rhs: &[F], g_norm_sq: F) -> F {
+    let m = rhs.len();
+    let scale = (0..m).fold(F::zero(), |acc, i| acc + gram[i][i]) / F::from_usize(m).unwrap();
+    let tiny = F::epsilon() * g_norm_sq.max(F::min_positive_value());
+
+    let mut points = vec![];
+    for p in -12..=2 {
+        let lambda = scale * F::from_f64(10f64.powi(p)).unwrap();
+        if let Some(gamma) = regularised_solve(gram, rhs, lambda) {
+            // ||g - Y gamma||^2 = ||g||^2 - 2 gamma . Y^T g + gamma . Y^T Y gamma
+            let mut residual = g_norm_sq;
+            for i in 0..m {
+                residual = residual - F::from_f64(2.).unwrap() * gamma[i] * rhs[i];
+                for j in 0..m {
+                    residual = residual + gamma[i] * gram[i][j] * gamma[j];
+                }
+            }
+            let solution = gamma.iter().fold(F::zero(), |acc, x| acc + x.powi(2));
+            points.push((
+                lambda,
+                residual.max(tiny).sqrt().ln(),
+                solution.max(tiny).sqrt().ln(),
+            ));
+        }
+    }
+
+    let mut best = (F::neg_infinity(), scale);
+    for window in points.windows(3) {
+        let (a, b, c) = (window[0], window[1], window[2]);
+        let cross = (b.1 - a.1) * (c.2 - b.2) - (b.2 - a.2) * (c.1 - b.1);
+        let lengths = ((b.1 - a.1).hypot(b.2 - a.2))
+            * ((c.1 - b.1).hypot(c.2 - b.2))
+            * ((c.1 - a.1).hypot(c.2 - a.2));
+        if lengths > F::zero() {
+            let curvature = F::from_f64(2.).unwrap() * cross / lengths;
+            if curvature > best.0 {
+                best = (curvature, b.0);
+            }
+        }
+    }
+    best.1
+}
+
+impl<P, F> Mixer<P> for TikhonovAndersonMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPNorm<P::Float>
+        + FPDot<P::Param, P::Float>
+        + FPHoldsNaN,
+    P: FixedPointProblem<Float = F>,
+    F: FPFloat,
+{
+    const NAME: &'static str = "Tikhonov-Regularised Anderson Mixing";
+
+    fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError> {
+        let (g, restart_evaluations) = self.history.start(op, state)?;
+        if self.history.len() > self.memory {
+            self.history.pop_front();
+        }
+
+        let mut gamma = vec![];
+        if !self.history.is_empty() {
+            let dg = self.history.get_dg();
+            let gram: Vec<Vec<F>> = dg
+                .iter()
+                .map(|yi| dg.iter().map(|yj| yi.dot(yj)).collect())
+                .collect();
+            let rhs: Vec<F> = dg.iter().map(|yi| yi.dot(&g)).collect();
+            let g_norm_sq = g.dot(&g);
+
+            self.regularisation = match self.rule {
+                RegularisationRule::ResidualScaled(factor) => factor * g_norm_sq,
+                RegularisationRule::LCurve => l_curve(&gram, &rhs, g_norm_sq),
+            };
+            debug!(
+                iteration = state.iter,
+                regularisation = %self.regularisation,
+                "Chosen Tikhonov regularisation"
+            );
+
+            // A singular system leaves the relaxed linear step
+            if let Some(solution) = regularised_solve(&gram, &rhs, self.regularisation) {
+                gamma = solution;
+            }
+        }
+
+        let new_x = self
+            .history
+            .extrapolate(&state.param, &g, self.beta, &gamma);
+        let (update, cost) = self.history.propose(op, state, g, &new_x)?;
+
+        Ok(IterData::new()
+            .cost(cost)
+            .param(new_x)
+            .evaluations(restart_evaluations + 1)
+            .update(update))
+    }
+
+    fn dampen(&mut self, factor: F) {
+        self.beta = self.beta * factor;
+    }
+
+    fn reset(&mut self) {
+        self.history.reset();
+    }
+}
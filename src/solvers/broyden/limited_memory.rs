@@ -0,0 +1,187 @@
+/*!
+Limited-Memory Broyden Mixer
+
+Broyden's second method for the residual g(x) = update(x) - x, in which the approximate inverse
+Jacobian is never formed. Starting from H0 = -beta I, the inverse Jacobian after the secant
+pairs s_i = x_{i+1} - x_i and y_i = g_{i+1} - g_i is held in the compact representation
+
+H = H0 + (S - H0 Y) M^{-1} Y^T
+
+where M is the upper-triangular part of Y^T Y. Only the last `memory` pairs are stored in a ring
+buffer, so storage is O(n m) and no square matrices are required.
+
+Reference: https://doi.org/10.1007/BF01582063 (Byrd, Nocedal & Schnabel)
+*/
+
+use crate::prelude::*;
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tracing::debug;
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Limited-memory Broyden mixer in compact representation
+pub struct LimitedMemoryBroydenMixer<F, P: FixedPointProblem> {
+    beta: F,
+    memory: usize,
+
+    /// Internal data
+    s_history: VecDeque<P::Param>,
+    y_history: VecDeque<P::Param>,
+    /// Column j holds y_i . y_j for the pairs i <= j
+    yty: VecDeque<VecDeque<F>>,
+    g: Option<P::Param>,
+    cached_iter: u64,
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> std::default::Default
+    for LimitedMemoryBroydenMixer<F, P>
+{
+    fn default() -> Self {
+        LimitedMemoryBroydenMixer::new()
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> LimitedMemoryBroydenMixer<F, P> {
+    /// Generate a new limited-memory Broyden mixer with default parameters
+    pub fn new() -> Self {
+        LimitedMemoryBroydenMixer {
+            beta: F::from_f64(1.).unwrap(),
+            memory: 10,
+            s_history: VecDeque::new(),
+            y_history: VecDeque::new(),
+            yty: VecDeque::new(),
+            g: None,
+            cached_iter: 0,
+        }
+    }
+
+    /// Factory method to set the relaxation beta, defining the initial inverse Jacobian
+    pub fn beta(mut self, beta: F) -> Self {
+        self.beta = beta;
+        self
+    }
+
+    /// Factory method to set the number of stored secant pairs
+    pub fn memory(mut self, memory: usize) -> Self {
+        self.memory = memory;
+        self
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> LimitedMemoryBroydenMixer<F, P>
+where
+    P::Param: FPDot<P::Param, P::Float>,
+{
+    /// Adds a secant pair to the ring buffer, discarding the oldest if it is full
+    fn push(&mut self, s: P::Param, y: P::Param, iter: u64) {
+        let column: VecDeque<F> = self
+            .y_history
+            .iter()
+            .chain(std::iter::once(&y))
+            .map(|yi| yi.dot(&y))
+            .collect();
+        let yy = column[column.len() - 1];
+        if yy.is_nan() | (yy <= F::epsilon()) {
+            debug!(iteration = iter, "Skipping degenerate secant pair");
+            return;
+        }
+        self.s_history.push_back(s);
+        self.y_history.push_back(y);
+        self.yty.push_back(column);
+        if self.y_history.len() > self.memory {
+            self.s_history.pop_front();
+            self.y_history.pop_front();
+            self.yty.pop_front();
+            for column in self.yty.iter_mut() {
+                column.pop_front();
+            }
+        }
+    }
+}
+
+impl<P, F> Mixer<P> for LimitedMemoryBroydenMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPNorm<P::Float>
+        + FPDot<P::Param, P::Float>
+        + FPHoldsNaN,
+    P: FixedPointProblem<Float = F>,
+    F: FPFloat,
+{
+    const NAME: &'static str = "Limited-Memory Broyden Mixing";
+
+    fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError> {
+        let x = state.get_param();
+        let mut evaluations = 1;
+        // Restart if the solver did not accept our last point
+        if self.g.is_none() | (self.cached_iter != state.iter) {
+            self.reset();
+            let fx = match op.update(&x) {
+                Ok(x) => x,
+                Err(e) => return Err(FixedPointError::update_failed(e, state.iter, x.norm())),
+            };
+            evaluations += 1;
+            self.g = Some(fx.sub(&x));
+        }
+        let g = self.g.take().unwrap();
+
+        // Solve M w = Y^T g by back substitution, M being upper triangular
+        let m = self.y_history.len();
+        let mut w: Vec<F> = self.y_history.iter().map(|yi| yi.dot(&g)).collect();
+        for j in (0..m).rev() {
+            w[j] = w[j] / self.yty[j][j];
+            let wj = w[j];
+            for (wi, mij) in w.iter_mut().zip(self.yty[j].iter()).take(j) {
+                *wi = *wi - *mij * wj;
+            }
+        }
+
+        // x - H g = x + beta g - (S + beta Y) w
+        let mut new_x = x.add(&g.mul(&self.beta));
+        for ((si, yi), wi) in self
+            .s_history
+            .iter()
+            .zip(self.y_history.iter())
+            .zip(w.iter())
+        {
+            new_x = new_x.sub(&si.add(&yi.mul(&self.beta)).mul(wi));
+        }
+
+        if new_x.holds_nan() {
+            return Err(FixedPointError::NumericalDivergence {
+                iteration: state.iter,
+            });
+        }
+
+        let fx = match op.update(&new_x) {
+            Ok(x) => x,
+            Err(e) => return Err(FixedPointError::update_failed(e, state.iter, new_x.norm())),
+        };
+        let new_g = fx.sub(&new_x);
+        let cost = new_g.norm();
+
+        self.push(new_x.sub(&x), new_g.sub(&g), state.iter);
+        self.g = Some(new_g);
+        self.cached_iter = state.iter + 1;
+
+        Ok(IterData::new()
+            .cost(cost)
+            .param(new_x)
+            .evaluations(evaluations)
+            .update(fx))
+    }
+
+    fn dampen(&mut self, factor: F) {
+        self.beta = self.beta * factor;
+    }
+
+    fn reset(&mut self) {
+        self.s_history.clear();
+        self.y_history.clear();
+        self.yty.clear();
+        self.g = None;
+    }
+}
@@ -0,0 +1,9 @@
+/*!
+Broyden Mixers
+*/
+
+pub mod limited_memory;
+pub mod multisecant;
+
+pub use self::limited_memory::*;
+pub use self::multisecant::*;
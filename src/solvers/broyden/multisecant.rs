@@ -0,0 +1,226 @@
+/*!
+Multisecant Broyden Mixer
+
+The generalised Broyden family of Fang & Saad. With residual g(x) = update(x) - x, secant
+differences S = [dx_i] and Y = [dg_i] over a window of recent iterates, and initial inverse
+Jacobian -beta I, every member of the family takes the step
+
+x+ = x + beta g - (S + beta Y) gamma,  gamma = (V^T Y)^{-1} V^T g
+
+The type-II (bad Broyden) choice V = Y reproduces Type-II Anderson mixing, while the type-I
+(good Broyden) choice V = S gives the multisecant Broyden method. The secant conditions can be
+weighted, following Johnson's modified Broyden method, by solving
+
+(W V^T Y W + w0^2 I) c = W V^T g,  gamma = W c
+
+with W the diagonal matrix of weights and w0 a small regularisation.
+
+Reference: https://doi.org/10.1002/nla.617 (Fang & Saad)
+*/
+
+use crate::prelude::*;
+use crate::solvers::history::SecantHistory;
+use crate::solvers::linalg;
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tracing::debug;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+/// Member of the generalised Broyden family
+pub enum SecantType {
+    /// Good Broyden, projecting the secant conditions onto the parameter differences
+    Type1,
+    /// Bad Broyden, projecting the secant conditions onto the residual differences
+    Type2,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+/// Weighting of the secant conditions in the window
+pub enum SecantWeighting<F> {
+    /// All secant conditions are weighted equally
+    Uniform,
+    /// Each secant condition is weighted by the inverse residual norm of its newer iterate
+    InverseResidual,
+    /// Secant conditions are weighted by the factor raised to their age, the newest having age zero
+    Geometric(F),
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Configurable multisecant mixer from the generalised Broyden family
+pub struct MultisecantMixer<F, P: FixedPointProblem> {
+    beta: F,
+    memory: usize,
+    kind: SecantType,
+    weighting: SecantWeighting<F>,
+    regularisation: F,
+
+    /// Internal data
+    #[serde(bound(
+        serialize = "P::Param: Serialize",
+        deserialize = "P::Param: Deserialize<'de>"
+    ))]
+    history: SecantHistory<P>,
+    residual_history: VecDeque<F>,
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> std::default::Default for MultisecantMixer<F, P> {
+    fn default() -> Self {
+        MultisecantMixer::new()
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> MultisecantMixer<F, P> {
+    /// Generate a new type-II multisecant mixer with uniform weights
+    pub fn new() -> Self {
+        MultisecantMixer {
+            beta: F::from_f64(1.).unwrap(),
+            memory: 5,
+            kind: SecantType::Type2,
+            weighting: SecantWeighting::Uniform,
+            regularisation: F::zero(),
+            history: SecantHistory::new(),
+            residual_history: VecDeque::new(),
+        }
+    }
+
+    /// Factory method to set the relaxation beta, defining the initial inverse Jacobian
+    pub fn beta(mut self, beta: F) -> Self {
+        self.beta = beta;
+        self
+    }
+
+    /// Factory method to set the window of secant conditions
+    pub fn memory(mut self, memory: usize) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// Factory method to choose the member of the Broyden family
+    pub fn kind(mut self, kind: SecantType) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Factory method to set the weighting of the secant conditions
+    pub fn weighting(mut self, weighting: SecantWeighting<F>) -> Self {
+        self.weighting = weighting;
+        self
+    }
+
+    /// Factory method to set the regularisation w0 of the weighted secant equations
+    pub fn regularisation(mut self, regularisation: F) -> Self {
+        self.regularisation = regularisation;
+        self
+    }
+
+    /// Returns the weights of the secant conditions, ordered from oldest to newest
+    fn weights(&self) -> Vec<F> {
+        let n = self.residual_history.len();
+        match self.weighting {
+            SecantWeighting::Uniform => vec![F::one(); n],
+            SecantWeighting::InverseResidual => {
+                self.residual_history.iter().map(|r| r.recip()).collect()
+            }
+            SecantWeighting::Geometric(factor) => {
+                (0..n).map(|i| factor.powi((n - 1 - i) as i32)).collect()
+            }
+        }
+    }
+
+    /// Discards the oldest secant condition
+    fn pop_front(&mut self) {
+        self.history.pop_front();
+        self.residual_history.pop_front();
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> MultisecantMixer<F, P>
+where
+    P::Param: FPDot<P::Param, P::Float>,
+{
+    /// Solves the weighted secant equations, discarding the oldest while they are singular
+    fn coefficients(&mut self, g: &P::Param, iter: u64) -> Vec<F> {
+        loop {
+            let n = self.history.len();
+            if n == 0 {
+                return vec![];
+            }
+            let weights = self.weights();
+            let projectors = match self.kind {
+                SecantType::Type1 => self.history.get_dx(),
+                SecantType::Type2 => self.history.get_dg(),
+            };
+            let mut matrix = vec![vec![F::zero(); n]; n];
+            for (i, (vi, wi)) in projectors.iter().zip(weights.iter()).enumerate() {
+                for (j, (yj, wj)) in self.history.get_dg().iter().zip(weights.iter()).enumerate() {
+                    matrix[i][j] = *wi * *wj * vi.dot(yj);
+                }
+                matrix[i][i] = matrix[i][i] + self.regularisation.powi(2);
+            }
+            let rhs = projectors
+                .iter()
+                .zip(weights.iter())
+                .map(|(vi, wi)| *wi * vi.dot(g))
+                .collect();
+
+            match linalg::solve(matrix, rhs) {
+                Some(c) => return c.iter().zip(weights.iter()).map(|(c, w)| *c * *w).collect(),
+                None => {
+                    debug!(
+                        iteration = iter,
+                        "Singular secant equations, dropping oldest pair"
+                    );
+                    self.pop_front();
+                }
+            }
+        }
+    }
+}
+
+impl<P, F> Mixer<P> for MultisecantMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPNorm<P::Float>
+        + FPDot<P::Param, P::Float>
+        + FPHoldsNaN,
+    P: FixedPointProblem<Float = F>,
+    F: FPFloat,
+{
+    const NAME: &'static str = "Multisecant Broyden Mixing";
+
+    fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError> {
+        let (g, restart_evaluations) = self.history.start(op, state)?;
+        // Clears the residual norms if the history restarted
+        self.residual_history.truncate(self.history.len());
+        if self.history.len() > self.residual_history.len() {
+            self.residual_history.push_back(g.norm());
+        }
+        if self.history.len() > self.memory {
+            self.pop_front();
+        }
+
+        let gamma = self.coefficients(&g, state.iter);
+        let new_x = self
+            .history
+            .extrapolate(&state.param, &g, self.beta, &gamma);
+        let (update, cost) = self.history.propose(op, state, g, &new_x)?;
+
+        Ok(IterData::new()
+            .cost(cost)
+            .param(new_x)
+            .evaluations(restart_evaluations + 1)
+            .update(update))
+    }
+
+    fn dampen(&mut self, factor: F) {
+        self.beta = self.beta * factor;
+    }
+
+    fn reset(&mut self) {
+        self.history.reset();
+        self.residual_history.clear();
+    }
+}
@@ -0,0 +1,7 @@
+/*!
+Chebyshev Acceleration
+*/
+
+pub mod semi_iterative;
+
+pub use self::semi_iterative::*;
@@ -0,0 +1,205 @@
+/*!
+Chebyshev Semi-Iterative Mixer
+
+For affine fixed point problems x = A x + b the residual g(x) = update(x) - x equals b - (I - A) x,
+so the fixed point solves a linear system. When the eigenvalues of A are real and lie in
+[lower, upper] with upper < 1, the three-term Chebyshev recurrence converges at the optimal rate
+attainable by any polynomial iteration on that interval, using one update per iteration.
+
+If no bounds are supplied the spectral radius of A is estimated from power iterations on the
+update map, and the symmetric interval [-rho, rho] widened by a safety factor is used.
+
+Reference: Saad, Iterative Methods for Sparse Linear Systems, Algorithm 12.1
+*/
+
+use crate::prelude::*;
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Chebyshev semi-iterative mixer for affine update maps
+pub struct ChebyshevMixer<F, P: FixedPointProblem> {
+    power_iterations: u64,
+    safety_factor: F,
+    /// Bounds on the eigenvalues of the linear part of the update
+    spectral_bounds: Option<(F, F)>,
+
+    /// Internal data
+    d: Option<P::Param>,
+    rho: F,
+    cached_iter: u64,
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> std::default::Default for ChebyshevMixer<F, P> {
+    fn default() -> Self {
+        ChebyshevMixer::new()
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> ChebyshevMixer<F, P> {
+    /// Generate a new Chebyshev mixer which estimates the spectral bounds
+    pub fn new() -> Self {
+        ChebyshevMixer {
+            power_iterations: 10,
+            safety_factor: F::from_f64(1.05).unwrap(),
+            spectral_bounds: None,
+            d: None,
+            rho: F::zero(),
+            cached_iter: 0,
+        }
+    }
+
+    /// Factory method to set bounds on the eigenvalues of the linear part of the update
+    pub fn spectral_bounds(mut self, lower: F, upper: F) -> Self {
+        self.spectral_bounds = Some((lower, upper));
+        self
+    }
+
+    /// Factory method to set the number of power iterations used to estimate the bounds
+    pub fn power_iterations(mut self, power_iterations: u64) -> Self {
+        self.power_iterations = power_iterations;
+        self
+    }
+
+    /// Factory method to set the factor by which the estimated spectral radius is widened
+    pub fn safety_factor(mut self, safety_factor: F) -> Self {
+        self.safety_factor = safety_factor;
+        self
+    }
+
+    /// Returns the spectral bounds in use, if they are known yet
+    pub fn get_spectral_bounds(&self) -> Option<(F, F)> {
+        self.spectral_bounds
+    }
+
+    /// Returns the centre and half-width of the spectrum of I - A
+    fn interval(&self) -> (F, F) {
+        let (lower, upper) = self.spectral_bounds.unwrap();
+        let two = F::from_f64(2.).unwrap();
+        let theta = F::one() - (upper + lower) / two;
+        let delta = (upper - lower) / two;
+        (theta, delta)
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> ChebyshevMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPDiv<P::Float, P::Param>
+        + FPNorm<P::Float>,
+{
+    /// Estimates the spectral radius of the linear part of the update by power iteration
+    fn estimate_bounds(
+        &mut self,
+        op: &mut P,
+        x: &P::Param,
+        fx: &P::Param,
+        iter: u64,
+    ) -> Result<u64, FixedPointError> {
+        let mut v = fx.sub(x);
+        let mut radius = F::zero();
+        let mut evaluations = 0;
+        for _ in 0..self.power_iterations {
+            let norm = v.norm();
+            if norm == F::zero() {
+                break;
+            }
+            v = v.div(&norm);
+            let trial = x.add(&v);
+            let shifted = match op.update(&trial) {
+                Ok(x) => x,
+                Err(e) => return Err(FixedPointError::update_failed(e, iter, trial.norm())),
+            };
+            evaluations += 1;
+            v = shifted.sub(fx);
+            radius = v.norm();
+        }
+        let radius = radius * self.safety_factor;
+        debug!(iteration = iter, radius = %radius, "Estimated spectral radius");
+        self.spectral_bounds = Some((-radius, radius));
+        Ok(evaluations)
+    }
+}
+
+impl<P, F> Mixer<P> for ChebyshevMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPDiv<P::Float, P::Param>
+        + FPNorm<P::Float>
+        + FPHoldsNaN,
+    P: FixedPointProblem<Float = F>,
+    F: FPFloat,
+{
+    const NAME: &'static str = "Chebyshev Semi-Iterative";
+
+    fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError> {
+        let x = state.get_param();
+        let mut evaluations = 1;
+        // Restart the recurrence if the solver did not accept our last point
+        if self.d.is_none() | (self.cached_iter != state.iter) {
+            self.reset();
+            let fx = match op.update(&x) {
+                Ok(x) => x,
+                Err(e) => return Err(FixedPointError::update_failed(e, state.iter, x.norm())),
+            };
+            evaluations += 1;
+            if self.spectral_bounds.is_none() {
+                evaluations += self.estimate_bounds(op, &x, &fx, state.iter)?;
+            }
+            let (lower, upper) = self.spectral_bounds.unwrap();
+            if (upper >= F::one()) | (lower > upper) {
+                return Err(FixedPointError::InvalidSpectralBounds);
+            }
+            let (theta, delta) = self.interval();
+            let r = fx.sub(&x);
+            self.rho = delta / theta;
+            self.d = Some(r.div(&theta));
+        }
+
+        let new_x = x.add(self.d.as_ref().unwrap());
+        if new_x.holds_nan() {
+            return Err(FixedPointError::NumericalDivergence {
+                iteration: state.iter,
+            });
+        }
+        let fx = match op.update(&new_x) {
+            Ok(x) => x,
+            Err(e) => return Err(FixedPointError::update_failed(e, state.iter, new_x.norm())),
+        };
+        let r = fx.sub(&new_x);
+        let cost = r.norm();
+
+        let (theta, delta) = self.interval();
+        let two = F::from_f64(2.).unwrap();
+        self.d = Some(if delta > F::zero() {
+            let rho = F::one() / (two * theta / delta - self.rho);
+            let d = self
+                .d
+                .as_ref()
+                .unwrap()
+                .mul(&(rho * self.rho))
+                .add(&r.mul(&(two * rho / delta)));
+            self.rho = rho;
+            d
+        } else {
+            // A single eigenvalue reduces the recurrence to an optimally damped linear step
+            r.div(&theta)
+        });
+        self.cached_iter = state.iter + 1;
+
+        Ok(IterData::new()
+            .cost(cost)
+            .param(new_x)
+            .evaluations(evaluations)
+            .update(fx))
+    }
+
+    fn reset(&mut self) {
+        self.d = None;
+    }
+}
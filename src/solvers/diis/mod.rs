@@ -0,0 +1,7 @@
+/*!
+Direct Inversion in the Iterative Subspace
+*/
+
+pub mod pulay;
+
+pub use self::pulay::*;
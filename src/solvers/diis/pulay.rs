@@ -0,0 +1,208 @@
+/*!
+Pulay DIIS Mixer
+
+Direct inversion in the iterative subspace. The coefficients of the extrapolation minimise the
+norm of the combined error vector subject to summing to one, which leads to the bordered system
+
+| B  -1 | | c |   |  0 |
+| -1  0 | | l | = | -1 |
+
+where B holds the inner products of the stored error vectors. The error vectors are supplied by
+`FixedPointProblem::error_vector`, falling back to the residual `update(x) - x`, while the
+extrapolation is applied to the stored parameters and their updates.
+
+Reference: https://doi.org/10.1016/0009-2614(80)80396-4
+*/
+
+use crate::prelude::*;
+use crate::solvers::linalg;
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tracing::debug;
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Pulay DIIS mixer with problem-defined error vectors
+pub struct DiisMixer<F, P: FixedPointProblem> {
+    beta: F,
+    memory: usize,
+
+    /// Internal data
+    x_history: VecDeque<P::Param>,
+    fx_history: VecDeque<P::Param>,
+    e_history: VecDeque<P::Param>,
+    current: Option<(P::Param, P::Param)>,
+    cached_iter: u64,
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> std::default::Default for DiisMixer<F, P> {
+    fn default() -> Self {
+        DiisMixer::new()
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> DiisMixer<F, P> {
+    /// Generate a new DIIS mixer with default parameters
+    pub fn new() -> Self {
+        DiisMixer {
+            beta: F::from_f64(1.).unwrap(),
+            memory: 6,
+            x_history: VecDeque::new(),
+            fx_history: VecDeque::new(),
+            e_history: VecDeque::new(),
+            current: None,
+            cached_iter: 0,
+        }
+    }
+
+    /// Factory method to set the relaxation beta
+    pub fn beta(mut self, beta: F) -> Self {
+        self.beta = beta;
+        self
+    }
+
+    /// Factory method to set the memory size for the mixer
+    pub fn memory(mut self, memory: usize) -> Self {
+        self.memory = memory;
+        self
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> DiisMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param> + FPDot<P::Param, P::Float> + FPNorm<P::Float>,
+{
+    /// Helper method to evaluate the update and error vector at a parameter
+    fn evaluate(
+        op: &mut P,
+        param: &P::Param,
+        iter: u64,
+    ) -> Result<(P::Param, P::Param), FixedPointError> {
+        let output = match op.update(param) {
+            Ok(x) => x,
+            Err(e) => return Err(FixedPointError::update_failed(e, iter, param.norm())),
+        };
+        let error = match op.error_vector(param, &output) {
+            Ok(Some(e)) => e,
+            Ok(None) => output.sub(param),
+            Err(e) => return Err(FixedPointError::error_vector_failed(e, iter, param.norm())),
+        };
+        Ok((output, error))
+    }
+
+    /// Solves the DIIS equations, discarding the oldest vectors while the system is singular
+    fn coefficients(&mut self, iter: u64) -> Vec<F> {
+        loop {
+            let n = self.e_history.len();
+            if n == 1 {
+                return vec![F::one()];
+            }
+            let mut b = vec![vec![-F::one(); n + 1]; n + 1];
+            b[n][n] = F::zero();
+            for (i, ei) in self.e_history.iter().enumerate() {
+                for (j, ej) in self.e_history.iter().enumerate().take(i + 1) {
+                    let bij = ei.dot(ej);
+                    b[i][j] = bij;
+                    b[j][i] = bij;
+                }
+            }
+            let mut rhs = vec![F::zero(); n + 1];
+            rhs[n] = -F::one();
+
+            match linalg::solve(b, rhs) {
+                Some(mut c) => {
+                    c.truncate(n);
+                    return c;
+                }
+                None => {
+                    debug!(
+                        iteration = iter,
+                        "Singular DIIS matrix, dropping oldest vector"
+                    );
+                    self.x_history.pop_front();
+                    self.fx_history.pop_front();
+                    self.e_history.pop_front();
+                }
+            }
+        }
+    }
+}
+
+impl<P, F> Mixer<P> for DiisMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPNorm<P::Float>
+        + FPDot<P::Param, P::Float>
+        + FPHoldsNaN,
+    P: FixedPointProblem<Float = F>,
+    F: FPFloat,
+{
+    const NAME: &'static str = "Pulay DIIS";
+
+    fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError> {
+        let x = state.get_param();
+        let mut evaluations = 1;
+        // Restart if the solver did not accept our last point
+        if self.current.is_none() | (self.cached_iter != state.iter) {
+            self.reset();
+            self.current = Some(Self::evaluate(op, &x, state.iter)?);
+            evaluations += 1;
+        }
+        let (fx, e) = self.current.take().unwrap();
+
+        self.x_history.push_back(x);
+        self.fx_history.push_back(fx);
+        self.e_history.push_back(e);
+        if self.e_history.len() > self.memory {
+            self.x_history.pop_front();
+            self.fx_history.pop_front();
+            self.e_history.pop_front();
+        }
+
+        let coefficients = self.coefficients(state.iter);
+        let one = F::one();
+        let mut new_x: Option<P::Param> = None;
+        for (c, (xi, fxi)) in coefficients
+            .iter()
+            .zip(self.x_history.iter().zip(self.fx_history.iter()))
+        {
+            let mixed = fxi.mul(&self.beta).add(&xi.mul(&(one - self.beta)));
+            let term = mixed.mul(c);
+            new_x = Some(match new_x {
+                Some(acc) => acc.add(&term),
+                None => term,
+            });
+        }
+        let new_x = new_x.unwrap();
+
+        if new_x.holds_nan() {
+            return Err(FixedPointError::NumericalDivergence {
+                iteration: state.iter,
+            });
+        }
+
+        let (new_fx, new_e) = Self::evaluate(op, &new_x, state.iter)?;
+        let cost = new_e.norm();
+        self.current = Some((new_fx.clone(), new_e));
+        self.cached_iter = state.iter + 1;
+
+        Ok(IterData::new()
+            .cost(cost)
+            .param(new_x)
+            .evaluations(evaluations)
+            .update(new_fx))
+    }
+
+    fn dampen(&mut self, factor: F) {
+        self.beta = self.beta * factor;
+    }
+
+    fn reset(&mut self) {
+        self.x_history.clear();
+        self.fx_history.clear();
+        self.e_history.clear();
+        self.current = None;
+    }
+}
@@ -0,0 +1,166 @@
+/*!
+History of iterate and residual differences shared by the Type-II mixers
+
+With residual g(x) = update(x) - x, the Type-II Anderson and multisecant mixers step from the
+current iterate to
+
+x+ = x + beta g - (dX + beta dG) gamma
+
+where the columns of dX and dG are the differences of consecutive iterates and residuals, and only
+the coefficients gamma differ between the mixers. The history caches the residual at the point it
+proposed, so the update is evaluated once per iteration unless the solver continues from a
+different point, in which case the history is restarted.
+*/
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Differences of recent iterates and residuals, with the residual at the proposed point
+pub(crate) struct SecantHistory<P: FixedPointProblem> {
+    dx: VecDeque<P::Param>,
+    dg: VecDeque<P::Param>,
+    x_prev: Option<P::Param>,
+    g_prev: Option<P::Param>,
+    g: Option<P::Param>,
+    cached_iter: u64,
+}
+
+impl<P: FixedPointProblem> SecantHistory<P> {
+    /// Generate an empty history
+    pub(crate) fn new() -> Self {
+        SecantHistory {
+            dx: VecDeque::new(),
+            dg: VecDeque::new(),
+            x_prev: None,
+            g_prev: None,
+            g: None,
+            cached_iter: 0,
+        }
+    }
+
+    /// Number of pairs of differences held
+    pub(crate) fn len(&self) -> usize {
+        self.dg.len()
+    }
+
+    /// Whether no differences are held
+    pub(crate) fn is_empty(&self) -> bool {
+        self.dg.is_empty()
+    }
+
+    /// Iterate differences, ordered from oldest to newest
+    pub(crate) fn get_dx(&self) -> &VecDeque<P::Param> {
+        &self.dx
+    }
+
+    /// Residual differences, ordered from oldest to newest
+    pub(crate) fn get_dg(&self) -> &VecDeque<P::Param> {
+        &self.dg
+    }
+
+    /// Discards the oldest pair of differences
+    pub(crate) fn pop_front(&mut self) {
+        self.dx.pop_front();
+        self.dg.pop_front();
+    }
+
+    /// Discards the pair of differences at the given index
+    pub(crate) fn remove(&mut self, idx: usize) {
+        self.dx.remove(idx);
+        self.dg.remove(idx);
+    }
+
+    /// Discards all differences and the cached residual
+    pub(crate) fn reset(&mut self) {
+        self.dx.clear();
+        self.dg.clear();
+        self.x_prev = None;
+        self.g_prev = None;
+        self.g = None;
+    }
+}
+
+impl<P: FixedPointProblem> SecantHistory<P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPNorm<P::Float>
+        + FPHoldsNaN,
+{
+    /// Returns the residual at the current iterate, adding the newest pair of differences
+    ///
+    /// The number of evaluations of the update made is returned alongside the residual
+    pub(crate) fn start(
+        &mut self,
+        op: &mut P,
+        state: &State<P>,
+    ) -> Result<(P::Param, u64), FixedPointError> {
+        let x = &state.param;
+        let mut evaluations = 0;
+        // Restart if the solver did not accept our last point
+        if self.g.is_none() | (self.cached_iter != state.iter) {
+            self.reset();
+            let fx = match op.update(x) {
+                Ok(fx) => fx,
+                Err(e) => return Err(FixedPointError::update_failed(e, state.iter, x.norm())),
+            };
+            evaluations += 1;
+            self.g = Some(fx.sub(x));
+        }
+        let g = self.g.take().unwrap();
+
+        if let (Some(x_prev), Some(g_prev)) = (self.x_prev.take(), self.g_prev.take()) {
+            self.dx.push_back(x.sub(&x_prev));
+            self.dg.push_back(g.sub(&g_prev));
+        }
+        Ok((g, evaluations))
+    }
+
+    /// Takes the step with the given coefficients, ordered from oldest to newest
+    pub(crate) fn extrapolate(
+        &self,
+        x: &P::Param,
+        g: &P::Param,
+        beta: P::Float,
+        gamma: &[P::Float],
+    ) -> P::Param {
+        let mut new_x = x.add(&g.mul(&beta));
+        for ((dx, dg), gi) in self.dx.iter().zip(self.dg.iter()).zip(gamma.iter()) {
+            new_x = new_x.sub(&dx.add(&dg.mul(&beta)).mul(gi));
+        }
+        new_x
+    }
+
+    /// Evaluates the residual at the proposed point, keeping it for the next iteration
+    ///
+    /// Returns the update at the proposed point and the norm of its residual
+    pub(crate) fn propose(
+        &mut self,
+        op: &mut P,
+        state: &State<P>,
+        g: P::Param,
+        new_x: &P::Param,
+    ) -> Result<(P::Param, P::Float), FixedPointError> {
+        if new_x.holds_nan() {
+            return Err(FixedPointError::NumericalDivergence {
+                iteration: state.iter,
+            });
+        }
+
+        let fx = match op.update(new_x) {
+            Ok(fx) => fx,
+            Err(e) => return Err(FixedPointError::update_failed(e, state.iter, new_x.norm())),
+        };
+        let new_g = fx.sub(new_x);
+        let cost = new_g.norm();
+
+        self.x_prev = Some(state.get_param());
+        self.g_prev = Some(g);
+        self.g = Some(new_g);
+        self.cached_iter = state.iter + 1;
+        Ok((fx, cost))
+    }
+}
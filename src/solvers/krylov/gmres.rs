@@ -0,0 +1,234 @@
+/*!
+Restarted GMRES for Affine Update Maps
+
+When the update is affine, update(x) = A x + b, the fixed point solves the linear system
+(I - A) x = b. The update is used as the only black-box operator, with b = update(0) and
+A v = update(v) - b, and each iteration of the mixer performs one restart cycle of GMRES.
+
+The mixer can optionally check that the update really is affine before solving, by comparing
+the update at the initial parameter with the average of the updates at two points symmetric
+about it.
+
+Reference: https://doi.org/10.1137/0907058 (Saad & Schultz)
+*/
+
+use crate::prelude::*;
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Restarted GMRES solver for affine fixed point problems
+pub struct GmresMixer<F, P: FixedPointProblem> {
+    restart: usize,
+    krylov_tolerance: F,
+    check_affine: bool,
+    affine_tolerance: F,
+
+    /// Internal data
+    b: Option<P::Param>,
+    r: Option<P::Param>,
+    cached_iter: u64,
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> std::default::Default for GmresMixer<F, P> {
+    fn default() -> Self {
+        GmresMixer::new()
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> GmresMixer<F, P> {
+    /// Generate a new GMRES solver with default parameters
+    pub fn new() -> Self {
+        GmresMixer {
+            restart: 20,
+            krylov_tolerance: F::from_f64(1e-10).unwrap(),
+            check_affine: false,
+            affine_tolerance: F::from_f64(1e-8).unwrap(),
+            b: None,
+            r: None,
+            cached_iter: 0,
+        }
+    }
+
+    /// Factory method to set the dimension of the Krylov subspace before restarting
+    pub fn restart(mut self, restart: usize) -> Self {
+        self.restart = restart;
+        self
+    }
+
+    /// Factory method to set the residual estimate at which a Krylov cycle ends early
+    pub fn krylov_tolerance(mut self, krylov_tolerance: F) -> Self {
+        self.krylov_tolerance = krylov_tolerance;
+        self
+    }
+
+    /// Factory method to enable the check that the update is affine
+    pub fn check_affine(mut self, check_affine: bool) -> Self {
+        self.check_affine = check_affine;
+        self
+    }
+
+    /// Factory method to set the relative tolerance of the affinity check
+    pub fn affine_tolerance(mut self, affine_tolerance: F) -> Self {
+        self.affine_tolerance = affine_tolerance;
+        self
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> GmresMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPDiv<P::Float, P::Param>
+        + FPNorm<P::Float>,
+{
+    /// Helper method to evaluate the update
+    fn evaluate(op: &mut P, param: &P::Param, iter: u64) -> Result<P::Param, FixedPointError> {
+        match op.update(param) {
+            Ok(x) => Ok(x),
+            Err(e) => Err(FixedPointError::update_failed(e, iter, param.norm())),
+        }
+    }
+
+    /// Checks that the update is affine along the initial residual
+    fn verify_affine(
+        &self,
+        op: &mut P,
+        x: &P::Param,
+        fx: &P::Param,
+        iter: u64,
+    ) -> Result<(), FixedPointError> {
+        let v = fx.sub(x);
+        let forward = Self::evaluate(op, &x.add(&v), iter)?;
+        let backward = Self::evaluate(op, &x.sub(&v), iter)?;
+        let two = F::from_f64(2.).unwrap();
+        let defect = forward.add(&backward).div(&two).sub(fx).norm();
+        debug!(iteration = iter, defect = %defect, "Checked affinity of update");
+        if defect > self.affine_tolerance * (fx.norm() + v.norm()) {
+            return Err(FixedPointError::NonAffineUpdate);
+        }
+        Ok(())
+    }
+}
+
+impl<P, F> Mixer<P> for GmresMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPDiv<P::Float, P::Param>
+        + FPNorm<P::Float>
+        + FPDot<P::Param, P::Float>
+        + FPZerosLike
+        + FPHoldsNaN,
+    P: FixedPointProblem<Float = F>,
+    F: FPFloat,
+{
+    const NAME: &'static str = "Restarted GMRES";
+
+    fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError> {
+        let x0 = state.get_param();
+        let mut evaluations = 0;
+        // The residual of an affine map is b - (I - A) x = update(x) - x
+        if self.r.is_none() | (self.cached_iter != state.iter) {
+            self.reset();
+            let fx = Self::evaluate(op, &x0, state.iter)?;
+            evaluations += 1;
+            if self.check_affine {
+                self.verify_affine(op, &x0, &fx, state.iter)?;
+                evaluations += 2;
+            }
+            self.r = Some(fx.sub(&x0));
+        }
+        if self.b.is_none() {
+            self.b = Some(Self::evaluate(op, &x0.zeros_like(), state.iter)?);
+            evaluations += 1;
+        }
+        let r0 = self.r.take().unwrap();
+        let b = self.b.as_ref().unwrap().clone();
+
+        let beta = r0.norm();
+        if beta == F::zero() {
+            self.r = Some(r0);
+            self.cached_iter = state.iter + 1;
+            return Ok(IterData::new()
+                .cost(beta)
+                .param(x0)
+                .evaluations(evaluations));
+        }
+        let mut basis = vec![r0.div(&beta)];
+        let mut hessenberg: Vec<Vec<F>> = vec![];
+        let mut rotations: Vec<(F, F)> = vec![];
+        let mut g = vec![beta];
+
+        for j in 0..self.restart {
+            // w = (I - A) v_j
+            let av = Self::evaluate(op, &basis[j], state.iter)?.sub(&b);
+            evaluations += 1;
+            let mut w = basis[j].sub(&av);
+            let mut h = vec![];
+            for v in basis.iter() {
+                let hij = v.dot(&w);
+                w = w.sub(&v.mul(&hij));
+                h.push(hij);
+            }
+            let h_next = w.norm();
+
+            for (i, &(c, s)) in rotations.iter().enumerate() {
+                let (hi, hk) = (h[i], h[i + 1]);
+                h[i] = c * hi + s * hk;
+                h[i + 1] = -s * hi + c * hk;
+            }
+            let denominator = (h[j] * h[j] + h_next * h_next).sqrt();
+            let (c, s) = (h[j] / denominator, h_next / denominator);
+            h[j] = denominator;
+            rotations.push((c, s));
+            g.push(-s * g[j]);
+            g[j] = c * g[j];
+            hessenberg.push(h);
+
+            let estimate = g[j + 1].abs();
+            if (estimate < self.krylov_tolerance) | (h_next == F::zero()) {
+                break;
+            }
+            basis.push(w.div(&h_next));
+        }
+
+        // Solve the triangular system H y = g, the columns of H stored in `hessenberg`
+        let k = hessenberg.len();
+        let mut y = g[..k].to_vec();
+        for j in (0..k).rev() {
+            y[j] = y[j] / hessenberg[j][j];
+            let yj = y[j];
+            for (yi, hij) in y.iter_mut().zip(hessenberg[j].iter()).take(j) {
+                *yi = *yi - *hij * yj;
+            }
+        }
+
+        let mut x = x0;
+        for (v, yj) in basis.iter().zip(y.iter()) {
+            x = x.add(&v.mul(yj));
+        }
+
+        if x.holds_nan() {
+            return Err(FixedPointError::NumericalDivergence {
+                iteration: state.iter,
+            });
+        }
+
+        let r = Self::evaluate(op, &x, state.iter)?.sub(&x);
+        evaluations += 1;
+        let cost = r.norm();
+        self.r = Some(r);
+        self.cached_iter = state.iter + 1;
+
+        Ok(IterData::new().cost(cost).param(x).evaluations(evaluations))
+    }
+
+    fn reset(&mut self) {
+        self.b = None;
+        self.r = None;
+    }
+}
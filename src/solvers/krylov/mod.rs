@@ -0,0 +1,7 @@
+/*!
+Krylov Subspace Solvers
+*/
+
+pub mod gmres;
+
+pub use self::gmres::*;
@@ -0,0 +1,82 @@
+/*!
+Dense linear algebra for the small systems assembled by the mixers
+
+These systems have the dimension of a mixer's memory rather than of the parameter vector, so
+they are stored as plain vectors of rows in the mixer's floating point type.
+*/
+
+use crate::prelude::FPFloat;
+
+/// Solves the square system `a x = b` by Gaussian elimination with partial pivoting
+///
+/// Returns `None` if the system is numerically singular
+pub(crate) fn solve<F: FPFloat>(mut a: Vec<Vec<F>>, mut b: Vec<F>) -> Option<Vec<F>> {
+    let n = b.len();
+    if a.iter().flat_map(|row| row.iter()).any(|x| !x.is_finite()) {
+        return None;
+    }
+    let scale = a
+        .iter()
+        .flat_map(|row| row.iter())
+        .fold(F::zero(), |acc, x| acc.max(x.abs()));
+    if scale == F::zero() {
+        return None;
+    }
+    let threshold = scale * F::epsilon() * F::from_usize(n).unwrap();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[i][col].abs().partial_cmp(&a[j][col].abs()).unwrap())
+            .unwrap();
+        if a[pivot][col].abs() <= threshold {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col].clone();
+        for row in col + 1..n {
+            let factor = a[row][col] / pivot_row[col];
+            for (ark, &pk) in a[row].iter_mut().zip(pivot_row.iter()).skip(col) {
+                *ark = *ark - factor * pk;
+            }
+            b[row] = b[row] - factor * b[col];
+        }
+    }
+
+    let mut x = vec![F::zero(); n];
+    for row in (0..n).rev() {
+        let mut sum = b[row];
+        for (&ark, &xk) in a[row].iter().zip(x.iter()).skip(row + 1) {
+            sum = sum - ark * xk;
+        }
+        x[row] = sum / a[row][row];
+    }
+    if x.iter().all(|x| x.is_finite()) {
+        Some(x)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let a = vec![vec![0., 2., 1.], vec![1., 1., 0.], vec![2., 0., 3.]];
+        let b = vec![7., 3., 11.];
+        let x: Vec<f64> = solve(a, b).unwrap();
+        let target = [1., 2., 3.];
+        for (xi, ti) in x.iter().zip(target.iter()) {
+            assert!((xi - ti).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_solve_singular() {
+        let a = vec![vec![1., 2.], vec![2., 4.]];
+        let b: Vec<f64> = vec![1., 2.];
+        assert!(solve(a, b).is_none());
+    }
+}
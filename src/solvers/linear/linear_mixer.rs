@@ -13,26 +13,23 @@ use serde::{Deserialize, Serialize};
 pub struct LinearMixer<F> {
     /// Relaxation parameter
     beta: F,
-    /// Tolerance target
-    tol: F,
-    /// Maximum iterations
-    max_iter: u64,
 }
 
 impl<F: FPFloat> std::default::Default for LinearMixer<F> {
     fn default() -> Self {
-        LinearMixer::new(F::from_f64(1.).unwrap(), F::from_f64(1e-6).unwrap(), 1000)
+        LinearMixer::new(F::from_f64(1.).unwrap())
     }
 }
 
 impl<F: FPFloat> LinearMixer<F> {
     /// Constructor
-    pub fn new(beta: F, tol: F, max_iter: u64) -> Self {
-        LinearMixer {
-            beta,
-            tol,
-            max_iter,
-        }
+    pub fn new(beta: F) -> Self {
+        LinearMixer { beta }
+    }
+
+    /// Returns the relaxation parameter
+    pub fn get_beta(&self) -> F {
+        self.beta
     }
 }
 
@@ -52,30 +49,28 @@ where
         let param = state.get_param();
         let output = match op.update(&param) {
             Ok(x) => x,
-            Err(_) => return Err(FixedPointError::UpdateFailed),
+            Err(e) => return Err(FixedPointError::update_failed(e, state.iter, param.norm())),
         };
         let new_param = output
             .mul(&self.beta)
             .add(&param.mul(&(F::from_f64(1.0).unwrap() - self.beta)));
 
         match new_param.holds_nan() {
-            true => return Err(FixedPointError::NumericalDivergence),
+            true => {
+                return Err(FixedPointError::NumericalDivergence {
+                    iteration: state.iter,
+                })
+            }
             false => (),
         };
 
         Ok(IterData::new()
             .cost(new_param.sub(&param).norm())
-            .param(new_param))
+            .param(new_param)
+            .evaluations(1))
     }
 
-    fn terminate(&mut self, state: &State<P>) -> Result<TerminationReason> {
-        let condition = if state.cost < self.tol {
-            TerminationReason::ToleranceBeaten
-        } else if state.iter > self.max_iter {
-            TerminationReason::HitMaxIterations
-        } else {
-            TerminationReason::NotTerminated
-        };
-        Ok(condition)
+    fn dampen(&mut self, factor: F) {
+        self.beta = self.beta * factor;
     }
 }
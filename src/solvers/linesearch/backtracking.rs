@@ -0,0 +1,171 @@
+/*!
+Backtracking Line Search
+
+This module wraps any mixer in an Armijo-style backtracking line search on the residual norm.
+The step proposed by the inner mixer is accepted when the residual at the trial point satisfies
+the sufficient-decrease condition ||g(x + a d)|| <= (1 - c a) ||g(x)||, otherwise the step length
+is contracted. If no step length is accepted the wrapper falls back to a relaxed linear update.
+*/
+
+use crate::prelude::*;
+use crate::solvers::safeguard::SafeguardCache;
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Backtracking line search wrapped around an inner mixer
+pub struct BacktrackingMixer<M, F, P: FixedPointProblem> {
+    /// The mixer proposing the step
+    inner: M,
+    /// Sufficient decrease constant
+    armijo: F,
+    /// Factor by which the step length is contracted on each backtrack
+    contraction: F,
+    /// Maximum number of contractions before falling back
+    max_backtracks: u64,
+    /// Relaxation parameter for the fallback linear update
+    beta: F,
+
+    /// Internal data, counting the update evaluations made by the line search
+    #[serde(bound(
+        serialize = "P::Param: Serialize",
+        deserialize = "P::Param: Deserialize<'de>"
+    ))]
+    cache: SafeguardCache<P>,
+}
+
+impl<M, F: FPFloat, P: FixedPointProblem<Float = F>> BacktrackingMixer<M, F, P> {
+    /// Wrap a mixer in a backtracking line search with default parameters
+    pub fn new(inner: M) -> Self {
+        BacktrackingMixer {
+            inner,
+            armijo: F::from_f64(1e-4).unwrap(),
+            contraction: F::from_f64(0.5).unwrap(),
+            max_backtracks: 10,
+            beta: F::from_f64(0.5).unwrap(),
+            cache: SafeguardCache::new(),
+        }
+    }
+
+    /// Factory method to set the sufficient decrease constant
+    pub fn armijo(mut self, armijo: F) -> Self {
+        self.armijo = armijo;
+        self
+    }
+
+    /// Factory method to set the step contraction factor
+    pub fn contraction(mut self, contraction: F) -> Self {
+        self.contraction = contraction;
+        self
+    }
+
+    /// Factory method to set the maximum number of backtracking steps
+    pub fn max_backtracks(mut self, max_backtracks: u64) -> Self {
+        self.max_backtracks = max_backtracks;
+        self
+    }
+
+    /// Factory method to set the relaxation of the fallback linear update
+    pub fn beta(mut self, beta: F) -> Self {
+        self.beta = beta;
+        self
+    }
+
+    /// Returns the number of update evaluations spent on line searches
+    pub fn get_extra_evaluations(&self) -> u64 {
+        self.cache.get_evaluations()
+    }
+
+    /// Returns a reference to the wrapped mixer
+    pub fn get_inner(&self) -> &M {
+        &self.inner
+    }
+}
+
+impl<M, P, F> Mixer<P> for BacktrackingMixer<M, F, P>
+where
+    M: Mixer<P>,
+    P: FixedPointProblem<Float = F>,
+    P::Param: FPMul<P::Float, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPSub<P::Param, P::Param>
+        + FPNorm<P::Float>
+        + FPHoldsNaN,
+    F: FPFloat,
+{
+    const NAME: &'static str = "Backtracking Line Search";
+
+    fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError> {
+        let param = state.get_param();
+        let (proposal, inner) = self.cache.propose(&mut self.inner, op, state)?;
+        let inner_evaluations = inner.get_evaluations().unwrap_or(1);
+        let step = proposal.sub(&param);
+        // The inner mixer may already have evaluated the update at the full step
+        let mut reuse = inner.get_update();
+
+        let one = F::from_f64(1.).unwrap();
+        let residual = self.cache.get_residual();
+        let mut alpha = one;
+        let mut accepted = None;
+        for _ in 0..=self.max_backtracks {
+            let trial = if alpha == one {
+                proposal.clone()
+            } else {
+                param.add(&step.mul(&alpha))
+            };
+            let evaluated = self
+                .cache
+                .evaluate_trial(op, &trial, reuse.take(), state.iter)?;
+            if let Some((f_trial, trial_residual)) = evaluated {
+                if trial_residual <= (one - self.armijo * alpha) * residual {
+                    accepted = Some((trial, f_trial, trial_residual));
+                    break;
+                }
+            }
+            alpha = alpha * self.contraction;
+        }
+
+        let (new_param, f_new, residual) = match accepted {
+            Some(x) => {
+                debug!(iteration = state.iter, step = %alpha, "Line search accepted step");
+                x
+            }
+            None => {
+                debug!(
+                    iteration = state.iter,
+                    "Line search failed, taking relaxed linear step"
+                );
+                let trial = self
+                    .cache
+                    .get_fx()
+                    .mul(&self.beta)
+                    .add(&param.mul(&(one - self.beta)));
+                let (f_trial, residual) = self.cache.evaluate(op, &trial, state.iter)?;
+                (trial, f_trial, residual)
+            }
+        };
+
+        // The inner mixer's history no longer describes the accepted point
+        if alpha < one {
+            self.inner.reset();
+        }
+
+        self.cache
+            .accept(new_param, f_new, residual, state, inner_evaluations)
+    }
+
+    fn terminate(&mut self, state: &State<P>) -> Result<TerminationReason> {
+        self.inner.terminate(state)
+    }
+
+    fn dampen(&mut self, factor: F) {
+        self.inner.dampen(factor);
+        self.beta = self.beta * factor;
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.cache.reset();
+    }
+}
@@ -0,0 +1,7 @@
+/*!
+Line Search Wrappers
+*/
+
+pub mod backtracking;
+
+pub use self::backtracking::*;
@@ -1,4 +1,16 @@
 //! Module for linear mixing algorithms
 
 pub mod anderson;
+pub mod broyden;
+pub mod chebyshev;
+pub mod diis;
+pub mod krylov;
 pub mod linear;
+pub mod linesearch;
+pub mod spectral;
+pub mod stochastic;
+pub mod trustregion;
+
+mod history;
+mod linalg;
+mod safeguard;
@@ -0,0 +1,183 @@
+/*!
+Update cache shared by the mixers safeguarding the step of an inner mixer
+
+The backtracking and trust-region wrappers judge a proposed step by the residual at the trial
+point against the residual at the current point. The update at the accepted point is kept for the
+next iteration, and the update the inner mixer evaluated at its own proposal is reused for the
+full step, so evaluations are only spent on shortened or fallback steps.
+
+When the current point was not accepted from the wrapper, its update is evaluated again by the
+restarting inner mixer as well. That duplicate is reported in the evaluations of the step, but not
+counted among the extra evaluations of the wrapper. Failures of the inner mixer are wrapped with
+its name, and an accepted point holding NaN is reported as divergence.
+*/
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Update at a point and the norm of its residual
+type Evaluation<P> = (
+    <P as FixedPointProblem>::Param,
+    <P as FixedPointProblem>::Float,
+);
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Update and residual norm at the current point, with the evaluations spent by the wrapper
+pub(crate) struct SafeguardCache<P: FixedPointProblem> {
+    fx: Option<P::Param>,
+    residual: P::Float,
+    cached_iter: u64,
+    evaluations: u64,
+    step_evaluations: u64,
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> SafeguardCache<P> {
+    /// Generate an empty cache
+    pub(crate) fn new() -> Self {
+        SafeguardCache {
+            fx: None,
+            residual: F::infinity(),
+            cached_iter: 0,
+            evaluations: 0,
+            step_evaluations: 0,
+        }
+    }
+
+    /// Returns the update at the current point
+    pub(crate) fn get_fx(&self) -> &P::Param {
+        self.fx.as_ref().unwrap()
+    }
+
+    /// Returns the residual norm at the current point
+    pub(crate) fn get_residual(&self) -> P::Float {
+        self.residual
+    }
+
+    /// Returns the number of update evaluations made by the wrapper at trial and fallback points
+    pub(crate) fn get_evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Discards the cached update
+    pub(crate) fn reset(&mut self) {
+        self.fx = None;
+    }
+}
+
+impl<P: FixedPointProblem> SafeguardCache<P>
+where
+    P::Param: FPSub<P::Param, P::Param> + FPNorm<P::Float> + FPHoldsNaN,
+{
+    /// Returns the step proposed by the inner mixer along with its iteration data
+    ///
+    /// The update at the current point is evaluated first, unless it was kept from the last step
+    pub(crate) fn propose<M: Mixer<P>>(
+        &mut self,
+        inner: &mut M,
+        op: &mut P,
+        state: &State<P>,
+    ) -> Result<(P::Param, IterData<P>), FixedPointError> {
+        self.start(op, state)?;
+        let data = match inner.next_iter(op, state) {
+            Ok(data) => data,
+            Err(e) => {
+                return Err(FixedPointError::MixerFailed {
+                    mixer: M::NAME.to_string(),
+                    iteration: state.iter,
+                    source: Box::new(e),
+                })
+            }
+        };
+        match data.get_param() {
+            Some(proposal) => Ok((proposal, data)),
+            None => Err(FixedPointError::UnexpectedOutcome),
+        }
+    }
+
+    /// Keeps the update at the accepted point for the next iteration and returns the step
+    ///
+    /// The evaluations of the step add those of the wrapper to the inner mixer's
+    pub(crate) fn accept(
+        &mut self,
+        param: P::Param,
+        fx: P::Param,
+        residual: P::Float,
+        state: &State<P>,
+        inner_evaluations: u64,
+    ) -> Result<IterData<P>, FixedPointError> {
+        if param.holds_nan() {
+            return Err(FixedPointError::NumericalDivergence {
+                iteration: state.iter,
+            });
+        }
+
+        self.fx = Some(fx.clone());
+        self.residual = residual;
+        self.cached_iter = state.iter + 1;
+
+        Ok(IterData::new()
+            .cost(residual)
+            .param(param)
+            .evaluations(inner_evaluations + self.step_evaluations)
+            .update(fx))
+    }
+
+    /// Evaluates the update at the current point unless it was kept from the last step
+    fn start(&mut self, op: &mut P, state: &State<P>) -> Result<(), FixedPointError> {
+        self.step_evaluations = 0;
+        // The cached update is only valid if the solver accepted our last point
+        if self.fx.is_none() | (self.cached_iter != state.iter) {
+            // The restarting inner mixer evaluates this point too, so it is not an extra
+            let (fx, residual) = Self::update(op, &state.param, state.iter)?;
+            self.step_evaluations += 1;
+            self.fx = Some(fx);
+            self.residual = residual;
+        }
+        Ok(())
+    }
+
+    /// Evaluates the update at a point, counting it against the wrapper
+    pub(crate) fn evaluate(
+        &mut self,
+        op: &mut P,
+        param: &P::Param,
+        iter: u64,
+    ) -> Result<Evaluation<P>, FixedPointError> {
+        let evaluation = Self::update(op, param, iter)?;
+        self.evaluations += 1;
+        self.step_evaluations += 1;
+        Ok(evaluation)
+    }
+
+    /// Evaluates the update and the norm of the residual at a point
+    fn update(op: &mut P, param: &P::Param, iter: u64) -> Result<Evaluation<P>, FixedPointError> {
+        let output = match op.update(param) {
+            Ok(x) => x,
+            Err(e) => return Err(FixedPointError::update_failed(e, iter, param.norm())),
+        };
+        let residual = output.sub(param).norm();
+        Ok((output, residual))
+    }
+
+    /// Evaluates the update at a trial point, reusing the update of the inner mixer if given
+    ///
+    /// Returns `None` without evaluating if the trial point holds NaN
+    pub(crate) fn evaluate_trial(
+        &mut self,
+        op: &mut P,
+        trial: &P::Param,
+        reuse: Option<P::Param>,
+        iter: u64,
+    ) -> Result<Option<Evaluation<P>>, FixedPointError> {
+        if trial.holds_nan() {
+            return Ok(None);
+        }
+        match reuse {
+            Some(fx) => {
+                let residual = fx.sub(trial).norm();
+                Ok(Some((fx, residual)))
+            }
+            None => self.evaluate(op, trial, iter).map(Some),
+        }
+    }
+}
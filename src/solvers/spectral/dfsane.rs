@@ -0,0 +1,268 @@
+/*!
+DF-SANE Mixer
+
+Derivative-free spectral residual method for the residual F(x) = x - f(x). Each iteration steps
+along d = -sigma F(x), with the Barzilai-Borwein length
+
+sigma = (s^T s) / (s^T y),  s = x_k - x_{k-1},  y = F(x_k) - F(x_{k-1})
+
+so a unit length recovers plain substitution. The step is accepted by a non-monotone line search
+on ||F||^2, which tries both signs of the direction and compares against the largest merit over a
+window of recent iterations, relaxed by a summable sequence.
+
+Reference: https://doi.org/10.1090/S0025-5718-06-01840-0 (La Cruz, Martinez & Raydan, 2006)
+*/
+
+use crate::prelude::*;
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tracing::debug;
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Spectral residual mixer with non-monotone line search
+pub struct DfSaneMixer<F, P: FixedPointProblem> {
+    window: usize,
+    sigma_min: F,
+    sigma_max: F,
+    gamma: F,
+    tau_min: F,
+    tau_max: F,
+    max_backtracks: usize,
+
+    /// Internal data
+    sigma: F,
+    merit_history: VecDeque<F>,
+    initial_norm: Option<F>,
+    k: u64,
+    x_prev: Option<P::Param>,
+    g_prev: Option<P::Param>,
+    g: Option<P::Param>,
+    cached_iter: u64,
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> std::default::Default for DfSaneMixer<F, P> {
+    fn default() -> Self {
+        DfSaneMixer::new()
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> DfSaneMixer<F, P> {
+    /// Generate a new DF-SANE mixer with the parameters of La Cruz et al.
+    pub fn new() -> Self {
+        DfSaneMixer {
+            window: 10,
+            sigma_min: F::from_f64(1e-10).unwrap(),
+            sigma_max: F::from_f64(1e10).unwrap(),
+            gamma: F::from_f64(1e-4).unwrap(),
+            tau_min: F::from_f64(0.1).unwrap(),
+            tau_max: F::from_f64(0.5).unwrap(),
+            max_backtracks: 20,
+            sigma: F::one(),
+            merit_history: VecDeque::new(),
+            initial_norm: None,
+            k: 0,
+            x_prev: None,
+            g_prev: None,
+            g: None,
+            cached_iter: 0,
+        }
+    }
+
+    /// Factory method to set the number of past merits in the non-monotone comparison
+    pub fn window(mut self, window: usize) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Factory method to set the bounds on the magnitude of the spectral step length
+    pub fn step_bounds(mut self, sigma_min: F, sigma_max: F) -> Self {
+        self.sigma_min = sigma_min;
+        self.sigma_max = sigma_max;
+        self
+    }
+
+    /// Factory method to set the sufficient decrease parameter of the line search
+    pub fn sufficient_decrease(mut self, gamma: F) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Factory method to set the bounds on the contraction of a rejected step
+    pub fn contraction_bounds(mut self, tau_min: F, tau_max: F) -> Self {
+        self.tau_min = tau_min;
+        self.tau_max = tau_max;
+        self
+    }
+
+    /// Factory method to set the maximum number of backtracking steps
+    pub fn max_backtracks(mut self, max_backtracks: usize) -> Self {
+        self.max_backtracks = max_backtracks;
+        self
+    }
+
+    /// Returns the spectral step length used at the last iteration
+    pub fn get_sigma(&self) -> F {
+        self.sigma
+    }
+
+    /// Spectral step length with the safeguard of La Cruz et al.
+    fn spectral_step(&self, ss: F, sy: F, residual: F) -> F {
+        let sigma = ss / sy;
+        if sigma.is_finite() & (sigma.abs() >= self.sigma_min) & (sigma.abs() <= self.sigma_max) {
+            sigma
+        } else if residual > F::one() {
+            F::one()
+        } else if residual >= F::from_f64(1e-5).unwrap() {
+            residual.recip()
+        } else {
+            F::from_f64(1e5).unwrap()
+        }
+    }
+
+    /// Safeguarded quadratic interpolation for the next trial step
+    fn contract(&self, alpha: F, merit: F, trial_merit: F) -> F {
+        let two = F::from_f64(2.).unwrap();
+        let candidate = alpha.powi(2) * merit / (trial_merit + (two * alpha - F::one()) * merit);
+        let lower = self.tau_min * alpha;
+        let upper = self.tau_max * alpha;
+        if candidate.is_nan() {
+            upper
+        } else {
+            candidate.max(lower).min(upper)
+        }
+    }
+}
+
+impl<P, F> Mixer<P> for DfSaneMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPNorm<P::Float>
+        + FPDot<P::Param, P::Float>
+        + FPHoldsNaN,
+    P: FixedPointProblem<Float = F>,
+    F: FPFloat,
+{
+    const NAME: &'static str = "DF-SANE Mixing";
+
+    fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError> {
+        let x = state.get_param();
+        let mut evaluations = 0;
+        // Restart if the solver did not accept our last point
+        if self.g.is_none() | (self.cached_iter != state.iter) {
+            self.reset();
+            let fx = match op.update(&x) {
+                Ok(x) => x,
+                Err(e) => return Err(FixedPointError::update_failed(e, state.iter, x.norm())),
+            };
+            evaluations += 1;
+            self.g = Some(fx.sub(&x));
+        }
+        let g = self.g.take().unwrap();
+        let merit = g.dot(&g);
+        let residual = merit.sqrt();
+        let initial_norm = *self.initial_norm.get_or_insert(residual);
+
+        // The residual is F = -g, so y = F_k - F_{k-1} = g_{k-1} - g_k
+        if let (Some(x_prev), Some(g_prev)) = (self.x_prev.take(), self.g_prev.take()) {
+            let s = x.sub(&x_prev);
+            let y = g_prev.sub(&g);
+            self.sigma = self.spectral_step(s.dot(&s), s.dot(&y), residual);
+        }
+
+        self.merit_history.push_back(merit);
+        if self.merit_history.len() > self.window.max(1) {
+            self.merit_history.pop_front();
+        }
+        let merit_max = self
+            .merit_history
+            .iter()
+            .fold(F::neg_infinity(), |acc, &m| acc.max(m));
+        let eta = initial_norm / F::from_u64((1 + self.k).pow(2)).unwrap();
+
+        // The direction -sigma F is sigma g
+        let direction = g.mul(&self.sigma);
+        let mut alpha_plus = F::one();
+        let mut alpha_minus = F::one();
+        let mut accepted = None;
+        for backtrack in 0..=self.max_backtracks {
+            let x_plus = x.add(&direction.mul(&alpha_plus));
+            let g_plus = match op.update(&x_plus) {
+                Ok(fx) => fx.sub(&x_plus),
+                Err(e) => return Err(FixedPointError::update_failed(e, state.iter, x_plus.norm())),
+            };
+            evaluations += 1;
+            let merit_plus = g_plus.dot(&g_plus);
+            if merit_plus <= merit_max + eta - self.gamma * alpha_plus.powi(2) * merit {
+                accepted = Some((x_plus, g_plus));
+                break;
+            }
+
+            let x_minus = x.sub(&direction.mul(&alpha_minus));
+            let g_minus = match op.update(&x_minus) {
+                Ok(fx) => fx.sub(&x_minus),
+                Err(e) => {
+                    return Err(FixedPointError::update_failed(
+                        e,
+                        state.iter,
+                        x_minus.norm(),
+                    ))
+                }
+            };
+            evaluations += 1;
+            let merit_minus = g_minus.dot(&g_minus);
+            if merit_minus <= merit_max + eta - self.gamma * alpha_minus.powi(2) * merit {
+                accepted = Some((x_minus, g_minus));
+                break;
+            }
+
+            if backtrack == self.max_backtracks {
+                debug!(
+                    iteration = state.iter,
+                    "Line search exhausted, taking the shortest positive step"
+                );
+                accepted = Some((x_plus, g_plus));
+                break;
+            }
+            alpha_plus = self.contract(alpha_plus, merit, merit_plus);
+            alpha_minus = self.contract(alpha_minus, merit, merit_minus);
+        }
+        let (new_x, new_g) = accepted.unwrap();
+
+        if new_x.holds_nan() {
+            return Err(FixedPointError::NumericalDivergence {
+                iteration: state.iter,
+            });
+        }
+        let cost = new_g.norm();
+
+        debug!(
+            iteration = state.iter,
+            sigma = %self.sigma,
+            "Took spectral step"
+        );
+
+        self.x_prev = Some(x);
+        self.g_prev = Some(g);
+        self.g = Some(new_g);
+        self.k += 1;
+        self.cached_iter = state.iter + 1;
+
+        Ok(IterData::new()
+            .cost(cost)
+            .param(new_x)
+            .evaluations(evaluations))
+    }
+
+    fn reset(&mut self) {
+        self.sigma = F::one();
+        self.merit_history.clear();
+        self.initial_norm = None;
+        self.k = 0;
+        self.x_prev = None;
+        self.g_prev = None;
+        self.g = None;
+    }
+}
@@ -0,0 +1,7 @@
+/*!
+Spectral Residual Methods
+*/
+
+pub mod dfsane;
+
+pub use self::dfsane::*;
@@ -0,0 +1,7 @@
+/*!
+Stochastic Approximation Mixers
+*/
+
+pub mod robbins_monro;
+
+pub use self::robbins_monro::*;
@@ -0,0 +1,279 @@
+/*!
+Robbins-Monro Mixer with Polyak-Ruppert Averaging
+
+Stochastic approximation for updates evaluated with noise, as in Monte Carlo SCF loops. The raw
+iterate takes decreasing relaxed steps,
+
+x_{k+1} = x_k + a_k (f(x_k) - x_k),  a_k = a_0 / (k + k_0)^alpha,  1/2 < alpha <= 1
+
+and after a burn-in the iterates are averaged. The averaged iterate is returned as the parameter,
+while the cost remains the noisy residual norm at the raw iterate. The cost therefore does not
+belong to the returned parameter: evaluating the residual at the average would double the number
+of updates and still carry the full noise of one update. The solver's best parameter, ranked by
+this cost, is an average picked by noise, so the final parameter should be used instead.
+
+The standard error of the average is estimated from non-overlapping batch means, and the mixer
+terminates once it falls below its tolerance, so the iteration stops when the average is
+statistically resolved rather than when the noisy residual is small. Successive iterates are strongly correlated, so the number
+of batches is capped and neighbouring batches are merged when the cap is reached, letting the
+batch length grow with the run.
+
+Reference: https://doi.org/10.1137/0330046 (Polyak & Juditsky, 1992)
+*/
+
+use crate::prelude::*;
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Stochastic approximation mixer returning the averaged iterate
+///
+/// The reported cost is the residual at the raw iterate, not at the returned average
+pub struct RobbinsMonroMixer<F, P: FixedPointProblem> {
+    step_size: F,
+    decay: F,
+    offset: F,
+    burn_in: u64,
+    batch_size: u64,
+    max_batches: usize,
+    tolerance: F,
+
+    /// Internal data
+    x: Option<P::Param>,
+    k: u64,
+    average: Option<P::Param>,
+    n_average: u64,
+    batch_sum: Option<P::Param>,
+    batch_count: u64,
+    current_batch_size: u64,
+    batch_means: Vec<P::Param>,
+    standard_error: F,
+    cached_iter: u64,
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> std::default::Default
+    for RobbinsMonroMixer<F, P>
+{
+    fn default() -> Self {
+        RobbinsMonroMixer::new()
+    }
+}
+
+impl<F: FPFloat, P: FixedPointProblem<Float = F>> RobbinsMonroMixer<F, P> {
+    /// Generate a new Robbins-Monro mixer with default parameters
+    pub fn new() -> Self {
+        RobbinsMonroMixer {
+            step_size: F::one(),
+            decay: F::from_f64(0.6).unwrap(),
+            offset: F::one(),
+            burn_in: 100,
+            batch_size: 50,
+            max_batches: 32,
+            tolerance: F::from_f64(1e-4).unwrap(),
+            x: None,
+            k: 0,
+            average: None,
+            n_average: 0,
+            batch_sum: None,
+            batch_count: 0,
+            current_batch_size: 50,
+            batch_means: vec![],
+            standard_error: F::infinity(),
+            cached_iter: 0,
+        }
+    }
+
+    /// Factory method to set the initial step size a_0
+    pub fn step_size(mut self, step_size: F) -> Self {
+        self.step_size = step_size;
+        self
+    }
+
+    /// Factory method to set the decay exponent alpha, which should lie in (1/2, 1]
+    pub fn decay(mut self, decay: F) -> Self {
+        self.decay = decay;
+        self
+    }
+
+    /// Factory method to set the offset k_0 delaying the decay of the step size
+    pub fn offset(mut self, offset: F) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Factory method to set the number of iterates discarded before averaging
+    pub fn burn_in(mut self, burn_in: u64) -> Self {
+        self.burn_in = burn_in;
+        self
+    }
+
+    /// Factory method to set the initial number of iterates in each batch of the error estimate
+    pub fn batch_size(mut self, batch_size: u64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self.current_batch_size = self.batch_size;
+        self
+    }
+
+    /// Factory method to set the number of batches at which neighbouring batches are merged
+    pub fn max_batches(mut self, max_batches: usize) -> Self {
+        self.max_batches = max_batches.max(4);
+        self
+    }
+
+    /// Factory method to set the standard error of the average at which the mixer terminates
+    ///
+    /// The tolerance is absolute, in the units of the parameter, and defaults to 1e-4
+    pub fn tolerance(mut self, tolerance: F) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Returns the estimated standard error of the average, infinite until enough batches exist
+    pub fn get_standard_error(&self) -> F {
+        self.standard_error
+    }
+
+    /// Returns the number of iterates in the average
+    pub fn get_averaged_iterates(&self) -> u64 {
+        self.n_average
+    }
+}
+
+impl<P, F> RobbinsMonroMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPDot<P::Param, P::Float>,
+    P: FixedPointProblem<Float = F>,
+    F: FPFloat,
+{
+    /// Stores a completed batch mean and refreshes the standard error
+    fn push_batch(&mut self, batch: P::Param) {
+        self.batch_means.push(batch);
+        if self.batch_means.len() >= self.max_batches {
+            let half = F::from_f64(0.5).unwrap();
+            self.batch_means = self
+                .batch_means
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => a.add(b).mul(&half),
+                    _ => pair[0].clone(),
+                })
+                .collect();
+            self.current_batch_size *= 2;
+        }
+
+        // Too few batches give an unreliable estimate of the spread
+        let m = self.batch_means.len();
+        self.standard_error = if m < self.max_batches / 2 {
+            F::infinity()
+        } else {
+            let m = F::from_usize(m).unwrap();
+            let mean = self
+                .batch_means
+                .iter()
+                .skip(1)
+                .fold(self.batch_means[0].clone(), |acc, b| acc.add(b))
+                .mul(&m.recip());
+            let spread = self.batch_means.iter().fold(F::zero(), |acc, b| {
+                let d = b.sub(&mean);
+                acc + d.dot(&d)
+            });
+            (spread / (m - F::one()) / m).sqrt()
+        };
+    }
+}
+
+impl<P, F> Mixer<P> for RobbinsMonroMixer<F, P>
+where
+    P::Param: FPSub<P::Param, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPMul<P::Float, P::Param>
+        + FPDot<P::Param, P::Float>
+        + FPNorm<P::Float>
+        + FPHoldsNaN,
+    P: FixedPointProblem<Float = F>,
+    F: FPFloat,
+{
+    const NAME: &'static str = "Robbins-Monro Mixing";
+
+    fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError> {
+        // Restart from the solver's parameter if it did not accept our last point
+        if self.x.is_none() | (self.cached_iter != state.iter) {
+            self.reset();
+            self.x = Some(state.get_param());
+        }
+        let x = self.x.take().unwrap();
+
+        let fx = match op.update(&x) {
+            Ok(x) => x,
+            Err(e) => return Err(FixedPointError::update_failed(e, state.iter, x.norm())),
+        };
+        let residual = fx.sub(&x);
+        // Residual at the raw iterate, which is not the returned average
+        let cost = residual.norm();
+        let a_k = self.step_size / (F::from_u64(self.k).unwrap() + self.offset).powf(self.decay);
+        let new_x = x.add(&residual.mul(&a_k));
+        if new_x.holds_nan() {
+            return Err(FixedPointError::NumericalDivergence {
+                iteration: state.iter,
+            });
+        }
+        self.k += 1;
+
+        if self.k > self.burn_in {
+            // Running mean of the iterates after burn-in
+            self.n_average += 1;
+            let weight = F::from_u64(self.n_average).unwrap().recip();
+            self.average = Some(match self.average.take() {
+                Some(average) => average.add(&new_x.sub(&average).mul(&weight)),
+                None => new_x.clone(),
+            });
+
+            self.batch_sum = Some(match self.batch_sum.take() {
+                Some(sum) => sum.add(&new_x),
+                None => new_x.clone(),
+            });
+            self.batch_count += 1;
+            if self.batch_count == self.current_batch_size {
+                let sum = self.batch_sum.take().unwrap();
+                self.push_batch(sum.mul(&F::from_u64(self.batch_count).unwrap().recip()));
+                self.batch_count = 0;
+            }
+        }
+
+        debug!(
+            iteration = state.iter,
+            step = %a_k,
+            standard_error = %self.standard_error,
+            "Took stochastic approximation step"
+        );
+
+        let param = self.average.clone().unwrap_or_else(|| new_x.clone());
+        self.x = Some(new_x);
+        self.cached_iter = state.iter + 1;
+
+        Ok(IterData::new().cost(cost).param(param).evaluations(1))
+    }
+
+    fn terminate(&mut self, _state: &State<P>) -> Result<TerminationReason> {
+        match self.standard_error < self.tolerance {
+            true => Ok(TerminationReason::ToleranceBeaten),
+            false => Ok(TerminationReason::NotTerminated),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.x = None;
+        self.k = 0;
+        self.average = None;
+        self.n_average = 0;
+        self.batch_sum = None;
+        self.batch_count = 0;
+        self.current_batch_size = self.batch_size;
+        self.batch_means.clear();
+        self.standard_error = F::infinity();
+    }
+}
@@ -0,0 +1,7 @@
+/*!
+Trust Region Wrappers
+*/
+
+pub mod trust_region;
+
+pub use self::trust_region::*;
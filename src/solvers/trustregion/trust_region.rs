@@ -0,0 +1,237 @@
+/*!
+Trust Region Step Control
+
+This module wraps any mixer in a trust-region safeguard. The norm of the step proposed by the
+inner mixer is capped at the current radius. The radius shrinks when the achieved reduction of
+the residual is poor compared to the predicted reduction, and grows when the reduction is good
+and the step was limited by the radius. The predicted reduction assumes the full proposed step
+annihilates the residual, so a truncated step of fraction t predicts a reduction t ||g(x)||.
+Rejected steps are replaced by a relaxed linear update, whose length also acts as a floor on the
+radius so the safeguard cannot stall the iteration.
+*/
+
+use crate::prelude::*;
+use crate::solvers::safeguard::SafeguardCache;
+use miette::Result;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+#[derive(Clone, Deserialize, Serialize)]
+/// Trust region safeguard wrapped around an inner mixer
+pub struct TrustRegionMixer<M, F, P: FixedPointProblem> {
+    /// The mixer proposing the step
+    inner: M,
+    /// Current trust region radius
+    radius: F,
+    /// Maximum trust region radius
+    max_radius: F,
+    /// Minimum ratio of actual to predicted reduction for a step to be accepted
+    eta_accept: F,
+    /// Ratio below which the radius is shrunk
+    eta_shrink: F,
+    /// Ratio above which the radius is grown
+    eta_grow: F,
+    /// Factor by which the radius is shrunk
+    shrink_factor: F,
+    /// Factor by which the radius is grown
+    grow_factor: F,
+    /// Relaxation parameter for the fallback linear update
+    beta: F,
+    /// Number of rejected steps
+    rejections: u64,
+
+    /// Internal data, counting the update evaluations made by the trust region
+    #[serde(bound(
+        serialize = "P::Param: Serialize",
+        deserialize = "P::Param: Deserialize<'de>"
+    ))]
+    cache: SafeguardCache<P>,
+}
+
+impl<M, F: FPFloat, P: FixedPointProblem<Float = F>> TrustRegionMixer<M, F, P> {
+    /// Wrap a mixer in a trust region with default parameters
+    pub fn new(inner: M) -> Self {
+        TrustRegionMixer {
+            inner,
+            radius: F::from_f64(1.).unwrap(),
+            max_radius: F::from_f64(1e2).unwrap(),
+            eta_accept: F::from_f64(1e-4).unwrap(),
+            eta_shrink: F::from_f64(0.25).unwrap(),
+            eta_grow: F::from_f64(0.75).unwrap(),
+            shrink_factor: F::from_f64(0.5).unwrap(),
+            grow_factor: F::from_f64(2.).unwrap(),
+            beta: F::from_f64(0.5).unwrap(),
+            rejections: 0,
+            cache: SafeguardCache::new(),
+        }
+    }
+
+    /// Factory method to set the initial trust region radius
+    pub fn initial_radius(mut self, radius: F) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Factory method to set the maximum trust region radius
+    pub fn max_radius(mut self, max_radius: F) -> Self {
+        self.max_radius = max_radius;
+        self
+    }
+
+    /// Factory method to set the acceptance threshold on the reduction ratio
+    pub fn eta_accept(mut self, eta_accept: F) -> Self {
+        self.eta_accept = eta_accept;
+        self
+    }
+
+    /// Factory method to set the ratio thresholds for shrinking and growing the radius
+    pub fn ratio_thresholds(mut self, eta_shrink: F, eta_grow: F) -> Self {
+        self.eta_shrink = eta_shrink;
+        self.eta_grow = eta_grow;
+        self
+    }
+
+    /// Factory method to set the factors by which the radius is shrunk and grown
+    pub fn radius_factors(mut self, shrink_factor: F, grow_factor: F) -> Self {
+        self.shrink_factor = shrink_factor;
+        self.grow_factor = grow_factor;
+        self
+    }
+
+    /// Factory method to set the relaxation of the fallback linear update
+    pub fn beta(mut self, beta: F) -> Self {
+        self.beta = beta;
+        self
+    }
+
+    /// Returns the current trust region radius
+    pub fn get_radius(&self) -> F {
+        self.radius
+    }
+
+    /// Returns the number of update evaluations spent by the trust region
+    pub fn get_extra_evaluations(&self) -> u64 {
+        self.cache.get_evaluations()
+    }
+
+    /// Returns the number of rejected steps
+    pub fn get_rejections(&self) -> u64 {
+        self.rejections
+    }
+
+    /// Returns a reference to the wrapped mixer
+    pub fn get_inner(&self) -> &M {
+        &self.inner
+    }
+}
+
+impl<M, F: FPFloat, P: FixedPointProblem<Float = F>> TrustRegionMixer<M, F, P>
+where
+    P::Param: FPNorm<P::Float> + FPMul<P::Float, P::Param>,
+{
+    /// Helper method to scale a step so its norm does not exceed the radius
+    ///
+    /// Returns the scaled step and the fraction of the step retained
+    fn clip(&self, step: &P::Param) -> (P::Param, F) {
+        let norm = step.norm();
+        if norm > self.radius {
+            let fraction = self.radius / norm;
+            (step.mul(&fraction), fraction)
+        } else {
+            (step.clone(), F::from_f64(1.).unwrap())
+        }
+    }
+}
+
+impl<M, P, F> Mixer<P> for TrustRegionMixer<M, F, P>
+where
+    M: Mixer<P>,
+    P: FixedPointProblem<Float = F>,
+    P::Param: FPMul<P::Float, P::Param>
+        + FPAdd<P::Param, P::Param>
+        + FPSub<P::Param, P::Param>
+        + FPNorm<P::Float>
+        + FPHoldsNaN,
+    F: FPFloat,
+{
+    const NAME: &'static str = "Trust Region";
+
+    fn next_iter(&mut self, op: &mut P, state: &State<P>) -> Result<IterData<P>, FixedPointError> {
+        let param = state.get_param();
+        let (proposal, inner) = self.cache.propose(&mut self.inner, op, state)?;
+        let inner_evaluations = inner.get_evaluations().unwrap_or(1);
+        let one = F::from_f64(1.).unwrap();
+        let (step, fraction) = self.clip(&proposal.sub(&param));
+        // The update evaluated by the inner mixer is only valid for the full step
+        let (trial, reuse) = if fraction < one {
+            (param.add(&step), None)
+        } else {
+            (proposal, inner.get_update())
+        };
+
+        // Trial points holding NaN are rejected without being evaluated
+        let evaluated = self.cache.evaluate_trial(op, &trial, reuse, state.iter)?;
+        let residual = self.cache.get_residual();
+        let predicted = fraction * residual;
+        let ratio = match &evaluated {
+            Some((_, trial_residual)) if predicted > F::zero() => {
+                (residual - *trial_residual) / predicted
+            }
+            _ => F::neg_infinity(),
+        };
+
+        // NaN ratios are treated as a failed step
+        let ratio = if ratio.is_nan() {
+            F::neg_infinity()
+        } else {
+            ratio
+        };
+        if ratio < self.eta_shrink {
+            self.radius = self.radius * self.shrink_factor;
+        } else if (ratio > self.eta_grow) & (fraction < one) {
+            self.radius = (self.radius * self.grow_factor).min(self.max_radius);
+        }
+        debug!(iteration = state.iter, ratio = %ratio, radius = %self.radius, "Trust region update");
+
+        let (new_param, f_new, residual) = match evaluated {
+            Some((f_trial, trial_residual)) if ratio >= self.eta_accept => {
+                if fraction < one {
+                    // The inner mixer's history no longer describes the accepted point
+                    self.inner.reset();
+                }
+                (trial, f_trial, trial_residual)
+            }
+            _ => {
+                debug!(
+                    iteration = state.iter,
+                    "Rejected step, taking relaxed linear step"
+                );
+                self.rejections += 1;
+                self.inner.reset();
+                let linear = self.cache.get_fx().sub(&param).mul(&self.beta);
+                // The radius is not allowed to fall below the length of a safe step
+                self.radius = self.radius.max(linear.norm()).min(self.max_radius);
+                let trial = param.add(&linear);
+                let (f_trial, residual) = self.cache.evaluate(op, &trial, state.iter)?;
+                (trial, f_trial, residual)
+            }
+        };
+
+        self.cache
+            .accept(new_param, f_new, residual, state, inner_evaluations)
+    }
+
+    fn terminate(&mut self, state: &State<P>) -> Result<TerminationReason> {
+        self.inner.terminate(state)
+    }
+
+    fn dampen(&mut self, factor: F) {
+        self.inner.dampen(factor);
+        self.beta = self.beta * factor;
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.cache.reset();
+    }
+}
@@ -0,0 +1,101 @@
+/*!
+Douglas-Rachford splitting as a fixed point problem
+
+For min f(x) + g(x) with proximal operators prox_f and prox_g the Douglas-Rachford map is
+
+T(z) = z + prox_g(2 prox_f(z) - z) - prox_f(z)
+
+whose fixed points z* give the primal solution x* = prox_f(z*). ADMM applied to
+min f(x) + g(y) subject to x = y is the same iteration with the roles of f and g exchanged, on the
+variable w = x + u where u is the scaled dual, so the primal solution is recovered as prox_g(w*).
+
+Reference: https://stanford.edu/~boyd/papers/pdf/scs_2.0_v_global.pdf
+*/
+
+use crate::prelude::*;
+use crate::splitting::ProximalOperator;
+use miette::Result;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::marker::PhantomData;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+/// Order in which the proximal operators are applied
+pub enum SplittingScheme {
+    /// Douglas-Rachford splitting, applying prox_f first
+    DouglasRachford,
+    /// ADMM, equivalent to Douglas-Rachford applying prox_g first
+    Admm,
+}
+
+/// Fixed point problem for the splitting of min f(x) + g(x)
+pub struct SplittingProblem<X, F, S, PF, PG> {
+    prox_f: PF,
+    prox_g: PG,
+    scheme: SplittingScheme,
+    _marker: PhantomData<(X, F, S)>,
+}
+
+impl<X, F, S, PF, PG> SplittingProblem<X, F, S, PF, PG>
+where
+    X: FPAdd<X, X> + FPSub<X, X>,
+    PF: ProximalOperator<X>,
+    PG: ProximalOperator<X>,
+{
+    /// Generate the Douglas-Rachford map from the proximal operators of f and g
+    pub fn new(prox_f: PF, prox_g: PG) -> Self {
+        SplittingProblem {
+            prox_f,
+            prox_g,
+            scheme: SplittingScheme::DouglasRachford,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Factory method to set the splitting scheme
+    pub fn scheme(mut self, scheme: SplittingScheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    /// Applies the proximal operator evaluated first by the scheme
+    fn apply_first(&mut self, values: &X) -> Result<X> {
+        match self.scheme {
+            SplittingScheme::DouglasRachford => self.prox_f.prox(values),
+            SplittingScheme::Admm => self.prox_g.prox(values),
+        }
+    }
+
+    /// Applies the proximal operator evaluated on the reflection
+    fn apply_second(&mut self, values: &X) -> Result<X> {
+        match self.scheme {
+            SplittingScheme::DouglasRachford => self.prox_g.prox(values),
+            SplittingScheme::Admm => self.prox_f.prox(values),
+        }
+    }
+
+    /// Recovers the primal solution from a fixed point of the map
+    pub fn primal(&mut self, fixed_point: &X) -> Result<X> {
+        self.apply_first(fixed_point)
+    }
+}
+
+impl<X, F, S, PF, PG> FixedPointProblem for SplittingProblem<X, F, S, PF, PG>
+where
+    X: Clone + Serialize + DeserializeOwned + FPAdd<X, X> + FPSub<X, X>,
+    F: FPFloat,
+    S: Clone + Serialize + DeserializeOwned,
+    PF: ProximalOperator<X>,
+    PG: ProximalOperator<X>,
+{
+    type Output = X;
+    type Param = X;
+    type Float = F;
+    type Square = S;
+
+    fn update(&mut self, values: &Self::Param) -> Result<Self::Param> {
+        let first = self.apply_first(values)?;
+        let reflected = first.add(&first).sub(values);
+        let second = self.apply_second(&reflected)?;
+        Ok(values.add(&second).sub(&first))
+    }
+}
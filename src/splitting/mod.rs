@@ -0,0 +1,11 @@
+/*!
+Operator splitting
+
+Fixed-point maps built from proximal operators, which can be driven by any mixer.
+*/
+
+pub mod douglas_rachford;
+pub mod proximal;
+
+pub use self::douglas_rachford::*;
+pub use self::proximal::*;
@@ -0,0 +1,26 @@
+/*!
+Proximal operators
+
+The proximal operator of a convex function h with step gamma is
+
+prox(v) = argmin_x h(x) + ||x - v||^2 / (2 gamma)
+
+The step is fixed by the user when the operator is constructed.
+*/
+
+use miette::Result;
+
+/// This trait needs to be implemented for the proximal operators entering a splitting
+pub trait ProximalOperator<X> {
+    /// Evaluates the proximal operator at a point
+    fn prox(&mut self, values: &X) -> Result<X>;
+}
+
+impl<X, C> ProximalOperator<X> for C
+where
+    C: FnMut(&X) -> Result<X>,
+{
+    fn prox(&mut self, values: &X) -> Result<X> {
+        self(values)
+    }
+}
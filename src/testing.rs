@@ -4,8 +4,12 @@ Tests for running algorithms
 use crate::prelude::*;
 use miette::Result;
 use ndarray::{Array1, Array2};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_distr::Normal;
 
 /// Simple test structure
+#[derive(Clone)]
 struct TestCase {
     _f: Array1<f64>,
 }
@@ -17,6 +21,15 @@ impl TestCase {
             _f: Array1::zeros(2),
         }
     }
+
+    /// Norm of the residual of the fixed point equation
+    fn residual(&mut self, values: &Array1<f64>) -> f64 {
+        (self.update(values).unwrap() - values)
+            .iter()
+            .map(|x| x.powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
 }
 
 /// Impl of a FixedPointProblem for the testcase
@@ -38,18 +51,166 @@ impl FixedPointProblem for TestCase {
     }
 }
 
+/// Test structure supplying its own DIIS error vectors
+struct ErrorVectorCase {
+    inner: TestCase,
+    error_evaluations: u64,
+}
+
+impl FixedPointProblem for ErrorVectorCase {
+    type Output = Array1<f64>;
+    type Param = Array1<f64>;
+    type Float = f64;
+    type Square = Array2<f64>;
+
+    fn update(&mut self, values: &Self::Param) -> Result<Self::Param> {
+        self.inner.update(values)
+    }
+
+    fn error_vector(
+        &mut self,
+        values: &Self::Param,
+        output: &Self::Param,
+    ) -> Result<Option<Self::Param>> {
+        self.error_evaluations += 1;
+        Ok(Some((output - values) * 2.))
+    }
+}
+
+/// Affine test structure, x = A x + b with A symmetric tridiagonal
+#[derive(Clone)]
+struct AffineCase {
+    a: Array2<f64>,
+    b: Array1<f64>,
+}
+
+impl AffineCase {
+    /// Generates the new affine test structure
+    fn new() -> AffineCase {
+        AffineCase::with_dimension(6)
+    }
+
+    /// Generates the affine test structure with the given number of unknowns
+    fn with_dimension(n: usize) -> AffineCase {
+        let mut a = Array2::zeros((n, n));
+        for i in 0..n - 1 {
+            a[(i, i + 1)] = 0.45;
+            a[(i + 1, i)] = 0.45;
+        }
+        AffineCase {
+            a,
+            b: Array1::ones(n),
+        }
+    }
+
+    /// Norm of the residual of the fixed point equation
+    fn residual(&self, values: &Array1<f64>) -> f64 {
+        (self.a.dot(values) + &self.b - values)
+            .iter()
+            .map(|x| x.powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+impl FixedPointProblem for AffineCase {
+    type Output = Array1<f64>;
+    type Param = Array1<f64>;
+    type Float = f64;
+    type Square = Array2<f64>;
+
+    fn update(&mut self, values: &Self::Param) -> Result<Self::Param> {
+        Ok(self.a.dot(values) + &self.b)
+    }
+}
+
+/// Affine test structure whose update carries Gaussian noise
+struct NoisyAffineCase {
+    inner: AffineCase,
+    noise: f64,
+    rng: ChaCha8Rng,
+}
+
+impl NoisyAffineCase {
+    /// Generates the noisy affine test structure with a seeded generator
+    fn new(noise: f64, seed: u64) -> NoisyAffineCase {
+        NoisyAffineCase {
+            inner: AffineCase::new(),
+            noise,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl FixedPointProblem for NoisyAffineCase {
+    type Output = Array1<f64>;
+    type Param = Array1<f64>;
+    type Float = f64;
+    type Square = Array2<f64>;
+
+    fn update(&mut self, values: &Self::Param) -> Result<Self::Param> {
+        let noise = Normal::new(0., self.noise).unwrap();
+        let exact = self.inner.update(values)?;
+        Ok(exact.mapv(|x| x + self.rng.sample(noise)))
+    }
+}
+
+/// Affine test structure whose update takes a fixed wall time
+struct SlowAffineCase {
+    inner: AffineCase,
+    delay: std::time::Duration,
+}
+
+impl FixedPointProblem for SlowAffineCase {
+    type Output = Array1<f64>;
+    type Param = Array1<f64>;
+    type Float = f64;
+    type Square = Array2<f64>;
+
+    fn update(&mut self, values: &Self::Param) -> Result<Self::Param> {
+        std::thread::sleep(self.delay);
+        self.inner.update(values)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::solvers::{anderson::Type1AndersonMixer, linear::LinearMixer};
+    use crate::solvers::{
+        anderson::{
+            FilteredAndersonMixer, RegularisationRule, Sketch, SketchedAndersonMixer,
+            TikhonovAndersonMixer, Type1AndersonMixer,
+        },
+        broyden::{LimitedMemoryBroydenMixer, MultisecantMixer, SecantType, SecantWeighting},
+        chebyshev::ChebyshevMixer,
+        diis::DiisMixer,
+        krylov::GmresMixer,
+        linear::LinearMixer,
+        linesearch::BacktrackingMixer,
+        spectral::DfSaneMixer,
+        stochastic::RobbinsMonroMixer,
+        trustregion::TrustRegionMixer,
+    };
+    use crate::splitting::{ProximalOperator, SplittingProblem, SplittingScheme};
+    use crate::tuning::{SearchSpace, SearchStrategy, Tuner, TuningConfig};
+    use std::sync::{Arc, Mutex};
+
+    /// Absolute residual tolerance or iteration limit, whichever is met first
+    fn criterion<P: FixedPointProblem<Float = f64>>(tol: f64, max_iter: u64) -> Criterion<P> {
+        Criterion::Any(vec![
+            Criterion::AbsoluteResidual(tol),
+            Criterion::MaxIterations(max_iter),
+        ])
+    }
 
     #[test]
     fn test_linear() {
         let mut cost = TestCase::new();
-        let mixer: LinearMixer<f64> = LinearMixer::new(0.1, std::f64::EPSILON, 1000);
+        let mixer: LinearMixer<f64> = LinearMixer::new(0.1);
 
         let init: Array1<f64> = Array1::ones(6);
-        let mut solver = FixedPointSolver::new(mixer, init);
+        let mut solver =
+            FixedPointSolver::new(mixer, init).criterion(criterion(std::f64::EPSILON, 1000));
 
         let result = solver.run(&mut cost).unwrap();
         println!("{}", result.get_param());
@@ -59,11 +220,1610 @@ mod tests {
     fn test_stable_anderson() {
         let mut cost = TestCase::new();
         let init: Array1<f64> = Array1::ones(6);
-        let mixer = Type1AndersonMixer::new(init.len(), std::f64::EPSILON, 1000);
+        let mixer = Type1AndersonMixer::new(init.len());
 
-        let mut solver = FixedPointSolver::new(mixer, init);
+        let mut solver =
+            FixedPointSolver::new(mixer, init).criterion(criterion(std::f64::EPSILON, 1000));
 
         let result = solver.run(&mut cost).unwrap();
         println!("{}", result.get_param());
     }
+
+    #[test]
+    fn test_backtracking_anderson() {
+        let mut cost = AffineCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let inner = Type1AndersonMixer::new(init.len());
+        let mixer = BacktrackingMixer::new(inner);
+
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(result.converged());
+        // Every full step reduces the affine residual and reuses the inner mixer's update, and
+        // the initial point is evaluated by the inner mixer too, so the line search adds nothing
+        assert_eq!(solver.get_mixer().get_extra_evaluations(), 0);
+    }
+
+    #[test]
+    fn test_trust_region_anderson() {
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let inner = Type1AndersonMixer::new(init.len());
+        let mixer = TrustRegionMixer::new(inner)
+            .initial_radius(0.1)
+            .max_radius(1.);
+
+        let mut solver =
+            FixedPointSolver::new(mixer, init).criterion(criterion(f64::EPSILON, 1000));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(result.converged());
+        // The small initial radius rejects some early steps, but not all of them
+        let mixer = solver.get_mixer();
+        assert!(mixer.get_rejections() > 0);
+        assert!(mixer.get_rejections() < result.get_iterations());
+        assert!(mixer.get_radius() <= 1.);
+    }
+
+    #[test]
+    fn test_filtered_anderson() {
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let mixer = FilteredAndersonMixer::new().beta(0.5);
+
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(result.converged());
+        // Near convergence the residual differences become nearly dependent
+        let dropped = solver.get_mixer().get_dropped_columns();
+        assert_eq!(dropped.len() as u64, result.get_iterations());
+        assert!(dropped.iter().any(|columns| !columns.is_empty()));
+
+        // Only the most recent iterations are kept when the record is capped
+        let mixer = FilteredAndersonMixer::new().beta(0.5).record_length(5);
+        let mut solver =
+            FixedPointSolver::new(mixer, Array1::ones(6)).criterion(criterion(1e-12, 1000));
+        solver.run(&mut cost).unwrap();
+        let capped = solver.get_mixer().get_dropped_columns();
+        assert!(capped.iter().eq(dropped.iter().skip(dropped.len() - 5)));
+    }
+
+    #[test]
+    fn test_diis() {
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let mixer = DiisMixer::new().beta(0.5);
+
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(result.converged());
+        assert!(cost.residual(&result.get_param()) < 1e-12);
+    }
+
+    #[test]
+    fn test_diis_error_vector() {
+        let mut cost = ErrorVectorCase {
+            inner: TestCase::new(),
+            error_evaluations: 0,
+        };
+        let init: Array1<f64> = Array1::ones(6);
+        let mixer = DiisMixer::new().beta(0.5);
+
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(result.converged());
+        assert!(cost.error_evaluations > 0);
+
+        // The scaled error vectors lead to the same fixed point as the plain residual
+        let mut plain = TestCase::new();
+        let mut solver = FixedPointSolver::new(DiisMixer::new().beta(0.5), Array1::ones(6))
+            .criterion(criterion(1e-12, 1000));
+        let reference = solver.run(&mut plain).unwrap();
+        let difference = result.get_param() - reference.get_param();
+        assert!(difference.iter().all(|x| x.abs() < 1e-10));
+    }
+
+    #[test]
+    fn test_diis_error_vector_failed() {
+        let mut cost = FailingErrorVectorCase(TestCase::new());
+        let init: Array1<f64> = Array1::ones(6);
+        let mut solver = FixedPointSolver::new(DiisMixer::new().beta(0.5), init)
+            .criterion(criterion(1e-12, 1000));
+
+        let error = solver.run(&mut cost).err().unwrap();
+        match error.get_error() {
+            FixedPointError::ErrorVectorFailed {
+                source, iteration, ..
+            } => {
+                assert_eq!(*iteration, 0);
+                assert!(source
+                    .get_report()
+                    .downcast_ref::<InnerSolveFailed>()
+                    .is_some());
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_limited_memory_broyden() {
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let mixer = LimitedMemoryBroydenMixer::new().beta(0.5).memory(4);
+
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(result.converged());
+    }
+
+    #[test]
+    fn test_chebyshev() {
+        let mut cost = AffineCase::new();
+        let init: Array1<f64> = Array1::zeros(6);
+        let mixer = ChebyshevMixer::new().spectral_bounds(-0.81, 0.81);
+
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-10, 1000));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(cost.residual(&result.get_param()) < 1e-8);
+    }
+
+    #[test]
+    fn test_chebyshev_estimated_bounds() {
+        let mut cost = AffineCase::new();
+        let init: Array1<f64> = Array1::zeros(6);
+        let mixer = ChebyshevMixer::new();
+
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-10, 1000));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(result.converged());
+        assert!(cost.residual(&result.get_param()) < 1e-8);
+        // The estimate widens the extreme eigenvalues +-0.9 cos(pi / 7) of A
+        let (lower, upper) = solver.get_mixer().get_spectral_bounds().unwrap();
+        let radius = 0.9 * (std::f64::consts::PI / 7.).cos();
+        assert!((-0.9..=-radius).contains(&lower));
+        assert!((radius..=0.9).contains(&upper));
+    }
+
+    #[test]
+    fn test_gmres() {
+        let mut cost = AffineCase::new();
+        let init: Array1<f64> = Array1::zeros(6);
+        let mixer = GmresMixer::new().restart(4).check_affine(true);
+
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-10, 1000));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(cost.residual(&result.get_param()) < 1e-8);
+    }
+
+    #[test]
+    fn test_gmres_non_affine() {
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let mut mixer = GmresMixer::new().check_affine(true);
+
+        let state = State::new(init);
+        assert!(matches!(
+            mixer.next_iter(&mut cost, &state),
+            Err(FixedPointError::NonAffineUpdate)
+        ));
+    }
+
+    #[test]
+    fn test_multisecant() {
+        for (kind, weighting) in [
+            (SecantType::Type1, SecantWeighting::Uniform),
+            (SecantType::Type2, SecantWeighting::Uniform),
+            (SecantType::Type2, SecantWeighting::InverseResidual),
+            (SecantType::Type2, SecantWeighting::Geometric(0.5)),
+        ] {
+            let mut cost = TestCase::new();
+            let init: Array1<f64> = Array1::ones(6);
+            let mixer = MultisecantMixer::new()
+                .beta(0.5)
+                .kind(kind)
+                .weighting(weighting)
+                .regularisation(1e-6);
+
+            let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+            let result = solver.run(&mut cost).unwrap();
+            assert!(result.converged(), "{:?} {:?}", kind, weighting);
+        }
+    }
+
+    #[test]
+    fn test_multisecant_type1_weightings() {
+        for weighting in [
+            SecantWeighting::Uniform,
+            SecantWeighting::InverseResidual,
+            SecantWeighting::Geometric(0.5),
+        ] {
+            let mut cost = AffineCase::new();
+            let init: Array1<f64> = Array1::zeros(6);
+            let mixer = MultisecantMixer::new()
+                .beta(0.5)
+                .kind(SecantType::Type1)
+                .weighting(weighting)
+                .regularisation(1e-6);
+
+            let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+            let result = solver.run(&mut cost).unwrap();
+            assert!(result.converged(), "{:?}", weighting);
+            assert!(cost.residual(&result.get_param()) < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_tikhonov_anderson() {
+        for rule in [
+            RegularisationRule::LCurve,
+            RegularisationRule::ResidualScaled(1e-3),
+        ] {
+            let mut cost = TestCase::new();
+            let init: Array1<f64> = Array1::ones(6);
+            let mixer = TikhonovAndersonMixer::new().beta(0.5).rule(rule);
+
+            let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+            let mut steps = solver.steps(&mut cost);
+            let mut residual = f64::INFINITY;
+            while let Some(state) = steps.next() {
+                let state = state.unwrap();
+                // The parameter is chosen once the history holds a residual difference
+                let regularisation = steps.get_solver().get_mixer().get_regularisation();
+                if state.iter > 1 {
+                    match rule {
+                        RegularisationRule::ResidualScaled(factor) => {
+                            let expected = factor * residual.powi(2);
+                            assert!((regularisation - expected).abs() <= 1e-12 * expected);
+                        }
+                        RegularisationRule::LCurve => {
+                            assert!(regularisation.is_finite() && regularisation > 0.)
+                        }
+                    }
+                }
+                residual = state.cost;
+            }
+            assert_eq!(
+                solver.get_state().termination_reason,
+                TerminationReason::ToleranceBeaten
+            );
+        }
+    }
+
+    #[test]
+    fn test_sketched_anderson_matches_unsketched() {
+        let n = 400;
+        let mut cost = AffineCase::with_dimension(n);
+        let mixer = SketchedAndersonMixer::new().memory(5).sketch(Sketch::Exact);
+        let mut solver =
+            FixedPointSolver::new(mixer, Array1::zeros(n)).criterion(criterion(1e-10, 1000));
+        let reference = solver.run(&mut cost).unwrap();
+        let reference_iters = solver.get_state().iter;
+        assert!(cost.residual(&reference.get_param()) < 1e-8);
+
+        for sketch in [
+            Sketch::SparseSign(40),
+            Sketch::Subsample(100),
+            Sketch::Gaussian(40),
+        ] {
+            let mixer = SketchedAndersonMixer::new()
+                .memory(5)
+                .sketch(sketch)
+                .seed(7);
+            let mut solver =
+                FixedPointSolver::new(mixer, Array1::zeros(n)).criterion(criterion(1e-10, 1000));
+
+            let result = solver.run(&mut cost).unwrap();
+            let error = (result.get_param() - reference.get_param())
+                .iter()
+                .map(|x| x.powi(2))
+                .sum::<f64>()
+                .sqrt();
+            assert!(result.converged(), "{:?}", sketch);
+            assert!(cost.residual(&result.get_param()) < 1e-8);
+            assert!(error < 1e-7, "{:?}", sketch);
+            assert!(
+                solver.get_state().iter <= 3 * reference_iters,
+                "{:?}",
+                sketch
+            );
+
+            // The stored sketch does not grow with the problem dimension
+            let operator =
+                serde_json::to_value(solver.get_mixer()).unwrap()["operator"].to_string();
+            assert!(operator.len() < 1000, "{}", operator);
+        }
+    }
+
+    #[test]
+    fn test_sketched_anderson_reproducible() {
+        let run = |seed| {
+            let mut cost = AffineCase::with_dimension(50);
+            let mixer = SketchedAndersonMixer::new()
+                .sketch(Sketch::Subsample(10))
+                .seed(seed);
+            let mut solver =
+                FixedPointSolver::new(mixer, Array1::zeros(50)).criterion(criterion(1e-10, 20));
+            let _ = solver.run(&mut cost);
+            solver.get_state().param.clone()
+        };
+        assert_eq!(run(3), run(3));
+    }
+
+    #[test]
+    fn test_portfolio() {
+        let cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+
+        let result = Portfolio::new()
+            .contender(
+                "linear",
+                FixedPointSolver::new(LinearMixer::new(0.01), init.clone())
+                    .criterion(criterion(1e-12, 100_000)),
+            )
+            .contender(
+                "anderson",
+                FixedPointSolver::new(TikhonovAndersonMixer::new(), init.clone())
+                    .criterion(criterion(1e-12, 1000)),
+            )
+            .contender(
+                "broyden",
+                FixedPointSolver::new(LimitedMemoryBroydenMixer::new(), init)
+                    .criterion(criterion(1e-12, 1000)),
+            )
+            .run(&cost);
+
+        let winner = result.get_winner().unwrap();
+        assert!(matches!(
+            result.get_reports()[winner].outcome,
+            ContenderOutcome::Converged
+        ));
+        assert!(result.get_reports()[winner].cost < 1e-12);
+        assert!(result.get_result().is_some());
+        // The slow linear mixer is stopped once another contender has converged
+        assert!(matches!(
+            result.get_reports()[0].outcome,
+            ContenderOutcome::Cancelled
+        ));
+    }
+
+    #[test]
+    fn test_portfolio_without_winner() {
+        let cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+
+        let result = Portfolio::new()
+            .contender(
+                "linear",
+                FixedPointSolver::new(LinearMixer::new(0.01), init.clone())
+                    .criterion(criterion(1e-12, 5)),
+            )
+            .contender(
+                "anderson",
+                FixedPointSolver::new(TikhonovAndersonMixer::new(), init)
+                    .criterion(criterion(1e-12, 2)),
+            )
+            .run(&cost);
+
+        assert!(result.get_winner().is_none());
+        assert!(result.get_result().is_none());
+        for report in result.get_reports() {
+            assert!(matches!(
+                report.outcome,
+                ContenderOutcome::Stopped(TerminationReason::HitMaxIterations)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_tune_linear() {
+        let cost = AffineCase::new();
+        let init: Array1<f64> = Array1::zeros(6);
+        let tuner = Tuner::new(SearchSpace::new(vec![0.1, 0.5, 0.9]), 30);
+
+        let result = tuner.tune_linear(&cost, &init).unwrap();
+        for trial in result.get_trials() {
+            assert!(trial.evaluations <= 30);
+        }
+        // The contraction of the relaxed map is fastest for the largest beta
+        assert_eq!(result.get_best().beta, 0.9);
+        assert_eq!(result.get_trials().len(), 3);
+
+        let json = serde_json::to_string(&result.get_best()).unwrap();
+        let config: TuningConfig<f64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(config, result.get_best());
+    }
+
+    #[test]
+    fn test_tune_anderson() {
+        let cost = AffineCase::with_dimension(50);
+        let init: Array1<f64> = Array1::zeros(50);
+        let tuner = Tuner::new(SearchSpace::new(vec![0.5, 1.0]).memory(vec![2, 5, 8]), 40)
+            .strategy(SearchStrategy::Random {
+                samples: 5,
+                seed: 11,
+            });
+
+        let result = tuner.tune_anderson(&cost, &init).unwrap();
+        assert_eq!(result.get_trials().len(), 5);
+        for trial in result.get_trials() {
+            assert!(trial.evaluations <= 40);
+        }
+
+        let best = result.get_best();
+        let mixer = Type1AndersonMixer::new(50)
+            .beta(best.beta)
+            .memory(best.memory.unwrap() as u64);
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-10, 1000));
+        let mut cost = cost;
+        let result = solver.run(&mut cost).unwrap();
+        assert!(cost.residual(&result.get_param()) < 1e-8);
+    }
+
+    #[test]
+    fn test_dfsane() {
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let mixer = DfSaneMixer::new();
+
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(result.converged());
+        assert!(cost.residual(&result.get_param()) < 1e-12);
+    }
+
+    #[test]
+    fn test_dfsane_affine() {
+        let mut cost = AffineCase::with_dimension(50);
+        let init: Array1<f64> = Array1::zeros(50);
+        let mixer = DfSaneMixer::new().window(5);
+
+        let mut solver =
+            FixedPointSolver::new(mixer, init.clone()).criterion(criterion(1e-10, 1000));
+        let result = solver.run(&mut cost).unwrap();
+        assert!(result.converged());
+        assert!(cost.residual(&result.get_param()) < 1e-8);
+        let spectral_iters = solver.get_state().iter;
+
+        let mut solver =
+            FixedPointSolver::new(LinearMixer::new(1.0), init).criterion(criterion(1e-10, 10_000));
+        solver.run(&mut cost).unwrap();
+        assert!(spectral_iters < solver.get_state().iter);
+    }
+
+    #[test]
+    fn test_robbins_monro() {
+        let mut exact = AffineCase::new();
+        let mut solver = FixedPointSolver::new(GmresMixer::new(), Array1::<f64>::zeros(6))
+            .criterion(criterion(1e-12, 100));
+        let reference = solver.run(&mut exact).unwrap().get_param();
+
+        let mut cost = NoisyAffineCase::new(0.02, 5);
+        let init: Array1<f64> = Array1::zeros(6);
+        let mixer = RobbinsMonroMixer::new().burn_in(1000).tolerance(3e-4);
+
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 100_000));
+        let result = solver.run(&mut cost).unwrap();
+        assert_eq!(
+            solver.get_state().termination_reason,
+            TerminationReason::ToleranceBeaten
+        );
+        let error = (result.get_param() - &reference)
+            .iter()
+            .map(|x| x.powi(2))
+            .sum::<f64>()
+            .sqrt();
+        // The average resolves the fixed point well below the noise on each update, which the
+        // residual reported as the cost still carries
+        let standard_error = solver.get_mixer().get_standard_error();
+        assert!(standard_error < 3e-4);
+        assert!(error < 5. * standard_error);
+        assert!(result.get_cost() > 0.02);
+
+        // The default tolerance also ends the run on the standard error
+        let mut cost = NoisyAffineCase::new(0.002, 5);
+        let mut solver =
+            FixedPointSolver::new(RobbinsMonroMixer::new().burn_in(1000), Array1::zeros(6))
+                .criterion(criterion(1e-12, 100_000));
+        let result = solver.run(&mut cost).unwrap();
+        assert_eq!(
+            result.get_termination_reason(),
+            TerminationReason::ToleranceBeaten
+        );
+        assert!(solver.get_mixer().get_standard_error() < 1e-4);
+    }
+
+    #[test]
+    fn test_splitting_lasso() {
+        // min 1/2 ||x - a||^2 + lambda ||x||_1, solved by soft thresholding a
+        let a: Array1<f64> = Array1::from(vec![3., -0.2, 0.5, -2., 0.05, 1.]);
+        let lambda = 0.4;
+        let exact = a.mapv(|x| x.signum() * (x.abs() - lambda).max(0.));
+
+        for scheme in [SplittingScheme::DouglasRachford, SplittingScheme::Admm] {
+            let target = a.clone();
+            let prox_f = move |v: &Array1<f64>| -> Result<Array1<f64>> { Ok((v + &target) / 2.) };
+            let prox_g = move |v: &Array1<f64>| -> Result<Array1<f64>> {
+                Ok(v.mapv(|x| x.signum() * (x.abs() - lambda).max(0.)))
+            };
+            let mut problem: SplittingProblem<_, f64, Array2<f64>, _, _> =
+                SplittingProblem::new(prox_f, prox_g).scheme(scheme);
+
+            let init: Array1<f64> = Array1::zeros(6);
+            let mixer = Type1AndersonMixer::new(init.len());
+            let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+            let result = solver.run(&mut problem).unwrap();
+            let primal = problem.primal(&result.get_param()).unwrap();
+            assert!((primal - &exact).iter().all(|x| x.abs() < 1e-8));
+        }
+    }
+
+    /// Projection onto the box [0, 1]^n
+    struct BoxProjection;
+
+    impl ProximalOperator<Array1<f64>> for BoxProjection {
+        fn prox(&mut self, values: &Array1<f64>) -> Result<Array1<f64>> {
+            Ok(values.mapv(|x| x.clamp(0., 1.)))
+        }
+    }
+
+    #[test]
+    fn test_splitting_feasibility() {
+        // Find a point in the box on the hyperplane sum(x) = 2
+        let hyperplane =
+            |v: &Array1<f64>| -> Result<Array1<f64>> { Ok(v - (v.sum() - 2.) / v.len() as f64) };
+        let mut problem: SplittingProblem<_, f64, Array2<f64>, _, _> =
+            SplittingProblem::new(BoxProjection, hyperplane);
+
+        let init: Array1<f64> = Array1::from(vec![3., -1., 0.5, 2., -0.5, 0.]);
+        let mixer = LinearMixer::new(0.5);
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 10_000));
+
+        let result = solver.run(&mut problem).unwrap();
+        let primal = problem.primal(&result.get_param()).unwrap();
+        assert!(primal.iter().all(|&x| (0. ..=1.).contains(&x)));
+        assert!((primal.sum() - 2.).abs() < 1e-8);
+    }
+
+    /// Observer recording the cost at every iteration
+    #[derive(Default)]
+    struct CostRecorder {
+        name: String,
+        costs: Vec<f64>,
+        finished: bool,
+    }
+
+    impl<P: FixedPointProblem<Float = f64>> Observer<P> for CostRecorder {
+        fn observe_init(&mut self, name: &str, _state: &State<P>) {
+            self.name = name.to_string();
+        }
+
+        fn observe_iter(&mut self, state: &State<P>, data: &IterData<P>) -> ObserverAction {
+            assert_eq!(data.get_cost(), Some(state.cost));
+            self.costs.push(state.cost);
+            ObserverAction::Continue
+        }
+
+        fn observe_final(&mut self, _state: &State<P>) {
+            self.finished = true;
+        }
+    }
+
+    /// Observer stopping the iteration after a fixed number of steps
+    struct StopAfter(u64);
+
+    impl<P: FixedPointProblem> Observer<P> for StopAfter {
+        fn observe_iter(&mut self, state: &State<P>, _data: &IterData<P>) -> ObserverAction {
+            match state.iter >= self.0 {
+                true => ObserverAction::Terminate,
+                false => ObserverAction::Continue,
+            }
+        }
+    }
+
+    #[test]
+    fn test_observers() {
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let mixer = LinearMixer::new(0.5);
+        let recorder = Arc::new(Mutex::new(CostRecorder::default()));
+
+        let mut solver = FixedPointSolver::new(mixer, init)
+            .criterion(criterion(1e-12, 1000))
+            .add_observer(recorder.clone());
+        solver.run(&mut cost).unwrap();
+
+        let recorder = recorder.lock().unwrap();
+        assert_eq!(recorder.name, "Linear Mixing");
+        assert_eq!(recorder.costs.len() as u64, solver.get_state().iter);
+        assert!(recorder.finished);
+    }
+
+    #[test]
+    fn test_observer_termination() {
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let mixer = LinearMixer::new(0.5);
+        let recorder = Arc::new(Mutex::new(CostRecorder::default()));
+
+        let mut solver = FixedPointSolver::new(mixer, init)
+            .criterion(criterion(1e-12, 1000))
+            .add_observer(StopAfter(5))
+            .add_observer(recorder.clone());
+        solver.run(&mut cost).unwrap();
+
+        assert_eq!(solver.get_state().iter, 5);
+        assert!(matches!(
+            solver.get_state().termination_reason,
+            TerminationReason::ObserverTerminated
+        ));
+        // Observers registered after the one requesting termination still see the last step
+        assert_eq!(recorder.lock().unwrap().costs.len(), 5);
+    }
+
+    #[test]
+    fn test_checkpoint_resume() {
+        for (format, name) in [
+            (CheckpointFormat::Json, "json"),
+            (CheckpointFormat::Bincode, "bincode"),
+        ] {
+            let path = std::env::temp_dir().join(format!(
+                "conflux_checkpoint_{}_{}",
+                std::process::id(),
+                name
+            ));
+            let init: Array1<f64> = Array1::ones(6);
+            let mixer = || Type1AndersonMixer::new(6);
+
+            let mut cost = TestCase::new();
+            let mut uninterrupted =
+                FixedPointSolver::new(mixer(), init.clone()).criterion(criterion(1e-12, 1000));
+            let expected = uninterrupted.run(&mut cost).unwrap();
+
+            // Interrupt a checkpointed run part way through
+            let mut cost = TestCase::new();
+            let mut interrupted = FixedPointSolver::new(mixer(), init)
+                .criterion(criterion(1e-12, 1000))
+                .checkpoint(Checkpoint::new(&path, CheckpointFrequency::Every(4)).format(format))
+                .add_observer(StopAfter(6));
+            interrupted.run(&mut cost).unwrap();
+
+            let mut cost = TestCase::new();
+            // Criteria are not stored in the checkpoint
+            let mut resumed: FixedPointSolver<TestCase, Type1AndersonMixer<f64, TestCase>> =
+                FixedPointSolver::from_checkpoint(&path, format)
+                    .unwrap()
+                    .criterion(criterion(1e-12, 1000));
+            assert_eq!(resumed.get_state().iter, 4);
+            let result = resumed.run(&mut cost).unwrap();
+
+            assert_eq!(resumed.get_state().iter, uninterrupted.get_state().iter);
+            assert_eq!(result.get_param(), expected.get_param());
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_missing() {
+        let path = std::env::temp_dir().join("conflux_checkpoint_does_not_exist");
+        let resumed: Result<FixedPointSolver<TestCase, LinearMixer<f64>>, _> =
+            FixedPointSolver::from_checkpoint(&path, CheckpointFormat::Json);
+        match resumed {
+            Err(FixedPointError::CheckpointFailed { source }) => {
+                let source = source.downcast_ref::<std::io::Error>().unwrap();
+                assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+            }
+            _ => panic!("missing checkpoint was read"),
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_interval() {
+        let path = std::env::temp_dir().join(format!(
+            "conflux_checkpoint_{}_interval",
+            std::process::id()
+        ));
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let interval = instant::Duration::from_secs(0);
+        let mut solver = FixedPointSolver::new(LinearMixer::new(0.5), init)
+            .criterion(criterion(1e-12, 1000))
+            .checkpoint(Checkpoint::new(
+                &path,
+                CheckpointFrequency::Interval(interval),
+            ))
+            .add_observer(StopAfter(3));
+        solver.run(&mut cost).unwrap();
+
+        let mut resumed: FixedPointSolver<TestCase, LinearMixer<f64>> =
+            FixedPointSolver::from_checkpoint(&path, CheckpointFormat::Json)
+                .unwrap()
+                .criterion(criterion(1e-12, 1000));
+        assert_eq!(resumed.get_state().iter, 3);
+
+        // The checkpoint was written on the iteration the observer stopped the run, which the
+        // resumed run does not repeat
+        let result = resumed.run(&mut cost).unwrap();
+        assert!(result.converged());
+        assert!(result.get_iterations() > 3);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_iteration_timing() {
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let mut solver =
+            FixedPointSolver::new(LinearMixer::new(0.5), init).criterion(criterion(1e-12, 1000));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert_eq!(
+            result.get_iteration_times().len() as u64,
+            solver.get_state().iter
+        );
+        let mixer_time: instant::Duration = result.get_iteration_times().iter().sum();
+        assert!(mixer_time <= result.get_time());
+        assert_eq!(solver.get_state().time, Some(result.get_time()));
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let mut cost = SlowAffineCase {
+            inner: AffineCase::new(),
+            delay: std::time::Duration::from_millis(5),
+        };
+        let init: Array1<f64> = Array1::zeros(6);
+        let limit = instant::Duration::from_millis(60);
+        let mut solver = FixedPointSolver::new(LinearMixer::new(0.1), init)
+            .criterion(Criterion::TimeLimit(limit));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(matches!(
+            solver.get_state().termination_reason,
+            TerminationReason::TimeLimitReached
+        ));
+        assert!(solver.get_state().iter > 0);
+        assert!(result.get_time() <= 2 * limit);
+    }
+
+    #[test]
+    fn test_criteria() {
+        let init: Array1<f64> = Array1::ones(6);
+        let cases: Vec<(Criterion<TestCase>, TerminationReason)> = vec![
+            (
+                Criterion::RelativeResidual(1e-6),
+                TerminationReason::RelativeToleranceBeaten,
+            ),
+            (
+                Criterion::StepSize(1e-10),
+                TerminationReason::StepToleranceBeaten,
+            ),
+            (
+                Criterion::MaxEvaluations(7),
+                TerminationReason::HitMaxEvaluations,
+            ),
+            (
+                Criterion::custom(|state| state.iter == 3),
+                TerminationReason::UserCriterion,
+            ),
+            (
+                Criterion::All(vec![
+                    Criterion::MaxIterations(5),
+                    Criterion::custom(|state| state.cost < 1e-3),
+                ]),
+                TerminationReason::HitMaxIterations,
+            ),
+            (
+                Criterion::Any(vec![
+                    Criterion::AbsoluteResidual(1e-300),
+                    Criterion::MaxIterations(5),
+                ]),
+                TerminationReason::HitMaxIterations,
+            ),
+        ];
+
+        for (criterion, expected) in cases {
+            let mut cost = TestCase::new();
+            let mut solver =
+                FixedPointSolver::new(LinearMixer::new(0.5), init.clone()).criterion(criterion);
+            solver.run(&mut cost).unwrap();
+
+            let state = solver.get_state();
+            assert_eq!(state.termination_reason, expected);
+            match expected {
+                TerminationReason::RelativeToleranceBeaten => {
+                    assert!(state.cost < 1e-6 * state.initial_cost)
+                }
+                TerminationReason::HitMaxEvaluations => assert_eq!(state.evaluations, 7),
+                TerminationReason::UserCriterion => assert_eq!(state.iter, 3),
+                _ => assert!(state.iter >= 5),
+            }
+        }
+    }
+
+    #[test]
+    fn test_stagnation() {
+        let mut cost = NoisyAffineCase::new(0.02, 3);
+        let init: Array1<f64> = Array1::zeros(6);
+        let mut solver =
+            FixedPointSolver::new(LinearMixer::new(0.5), init).criterion(Criterion::Any(vec![
+                Criterion::Stagnation(10),
+                Criterion::MaxIterations(10_000),
+            ]));
+        solver.run(&mut cost).unwrap();
+
+        let state = solver.get_state();
+        assert_eq!(state.termination_reason, TerminationReason::Stagnated);
+        assert_eq!(state.iter - state.last_best_iter, 10);
+    }
+
+    #[test]
+    fn test_evaluation_count() {
+        let mut cost = AffineCase::new();
+        let init: Array1<f64> = Array1::zeros(6);
+        let mixer = GmresMixer::new().restart(4);
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-10, 1000));
+        solver.run(&mut cost).unwrap();
+
+        // Each restart cycle evaluates the update once per Krylov vector and once for the residual
+        let state = solver.get_state();
+        assert!(state.evaluations > state.iter);
+        assert!(state.evaluations <= 2 + 5 * state.iter);
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let mut cost = SlowAffineCase {
+            inner: AffineCase::new(),
+            delay: std::time::Duration::from_millis(2),
+        };
+        let init: Array1<f64> = Array1::zeros(6);
+        let mut solver = FixedPointSolver::new(LinearMixer::new(0.01), init.clone())
+            .criterion(Criterion::AbsoluteResidual(1e-300));
+
+        let token = solver.get_cancellation_token();
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(30));
+            token.cancel();
+        });
+        let result = solver.run(&mut cost).unwrap();
+        canceller.join().unwrap();
+
+        assert_eq!(
+            result.get_termination_reason(),
+            TerminationReason::Cancelled
+        );
+        assert!(solver.get_state().iter > 0);
+        assert!(cost.inner.residual(&result.get_best_param()) < cost.inner.residual(&init));
+    }
+
+    /// Problem whose update blocks after a number of calls until it is cancelled
+    struct InterruptibleCase {
+        inner: AffineCase,
+        calls: u64,
+        block_after: u64,
+        token: Option<CancellationToken>,
+    }
+
+    impl FixedPointProblem for InterruptibleCase {
+        type Output = Array1<f64>;
+        type Param = Array1<f64>;
+        type Float = f64;
+        type Square = Array2<f64>;
+
+        fn update(&mut self, values: &Self::Param) -> Result<Self::Param> {
+            self.calls += 1;
+            if self.calls > self.block_after {
+                let token = self.token.as_ref().unwrap();
+                while !token.is_cancelled() {
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                }
+                return Err(miette::miette!("update interrupted"));
+            }
+            self.inner.update(values)
+        }
+
+        fn set_cancellation_token(&mut self, token: CancellationToken) {
+            self.token = Some(token);
+        }
+    }
+
+    #[test]
+    fn test_cancellation_during_update() {
+        let mut cost = InterruptibleCase {
+            inner: AffineCase::new(),
+            calls: 0,
+            block_after: 5,
+            token: None,
+        };
+        let token = CancellationToken::new();
+        let mut solver = FixedPointSolver::new(LinearMixer::new(0.5), Array1::zeros(6))
+            .cancellation_token(token.clone());
+
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            token.cancel();
+        });
+        let result = solver.run(&mut cost).unwrap();
+        canceller.join().unwrap();
+
+        // The interrupted update is abandoned, leaving the state of the last complete iteration
+        assert_eq!(
+            result.get_termination_reason(),
+            TerminationReason::Cancelled
+        );
+        assert_eq!(solver.get_state().iter, 5);
+        assert_eq!(result.get_best_param(), solver.get_state().best_param);
+    }
+
+    #[derive(thiserror::Error, miette::Diagnostic, Debug)]
+    #[error("inner solve did not converge")]
+    #[diagnostic(code(testing::inner_solve), help("loosen the inner tolerance"))]
+    /// Diagnostic raised by a failing update
+    struct InnerSolveFailed;
+
+    /// Problem whose error vector fails with a diagnostic
+    struct FailingErrorVectorCase(TestCase);
+
+    impl FixedPointProblem for FailingErrorVectorCase {
+        type Output = Array1<f64>;
+        type Param = Array1<f64>;
+        type Float = f64;
+        type Square = Array2<f64>;
+
+        fn update(&mut self, values: &Self::Param) -> Result<Self::Param> {
+            self.0.update(values)
+        }
+
+        fn error_vector(
+            &mut self,
+            _values: &Self::Param,
+            _output: &Self::Param,
+        ) -> Result<Option<Self::Param>> {
+            Err(InnerSolveFailed.into())
+        }
+    }
+
+    /// Problem whose update fails with a diagnostic after a number of calls
+    struct FailingCase {
+        inner: TestCase,
+        calls: u64,
+        fail_after: u64,
+    }
+
+    impl FixedPointProblem for FailingCase {
+        type Output = Array1<f64>;
+        type Param = Array1<f64>;
+        type Float = f64;
+        type Square = Array2<f64>;
+
+        fn update(&mut self, values: &Self::Param) -> Result<Self::Param> {
+            self.calls += 1;
+            if self.calls > self.fail_after {
+                return Err(InnerSolveFailed.into());
+            }
+            self.inner.update(values)
+        }
+    }
+
+    #[test]
+    fn test_update_error_source() {
+        use miette::Diagnostic;
+
+        let mut cost = FailingCase {
+            inner: TestCase::new(),
+            calls: 0,
+            fail_after: 0,
+        };
+        let mut mixer = BacktrackingMixer::new(LinearMixer::new(0.5));
+        let mut state = State::new(Array1::ones(6));
+        state.iter = 4;
+
+        // The backtracking mixer's own evaluation fails before the inner mixer runs
+        let error = mixer.next_iter(&mut cost, &state).err().unwrap();
+        match &error {
+            FixedPointError::UpdateFailed {
+                source,
+                iteration,
+                param_norm,
+            } => {
+                assert_eq!(*iteration, 4);
+                assert!((param_norm - 6f64.sqrt()).abs() < 1e-12);
+                assert_eq!(source.to_string(), "inner solve did not converge");
+                assert_eq!(
+                    source.help().map(|help| help.to_string()),
+                    Some("loosen the inner tolerance".to_string())
+                );
+            }
+            _ => panic!("unexpected error {:?}", error),
+        }
+
+        let mut report = String::new();
+        miette::GraphicalReportHandler::new()
+            .render_report(&mut report, &error)
+            .unwrap();
+        assert!(report.contains("inner solve did not converge"));
+    }
+
+    #[test]
+    fn test_wrapped_mixer_error() {
+        let mut cost = FailingCase {
+            inner: TestCase::new(),
+            calls: 0,
+            fail_after: 1,
+        };
+        let mut mixer = BacktrackingMixer::new(LinearMixer::new(0.5));
+        let state = State::new(Array1::ones(6));
+
+        let error = mixer.next_iter(&mut cost, &state).err().unwrap();
+        match &error {
+            FixedPointError::MixerFailed { mixer, source, .. } => {
+                assert_eq!(mixer, "Linear Mixing");
+                assert!(matches!(**source, FixedPointError::UpdateFailed { .. }));
+            }
+            _ => panic!("unexpected error {:?}", error),
+        }
+        // The full chain of causes is available through the standard error trait
+        let chain: Vec<String> =
+            std::iter::successors(Some(&error as &dyn std::error::Error), |e| e.source())
+                .map(|e| e.to_string())
+                .collect();
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain[2], "inner solve did not converge");
+    }
+
+    /// Problem whose update produces NaN values once, after a number of calls
+    struct DivergingCase {
+        inner: TestCase,
+        calls: u64,
+        diverge_after: u64,
+    }
+
+    impl FixedPointProblem for DivergingCase {
+        type Output = Array1<f64>;
+        type Param = Array1<f64>;
+        type Float = f64;
+        type Square = Array2<f64>;
+
+        fn update(&mut self, values: &Self::Param) -> Result<Self::Param> {
+            self.calls += 1;
+            match self.calls == self.diverge_after + 1 {
+                true => Ok(Array1::from_elem(values.len(), f64::NAN)),
+                false => self.inner.update(values),
+            }
+        }
+    }
+
+    /// Problem leaving the update unimplemented
+    struct NoUpdateCase;
+
+    impl FixedPointProblem for NoUpdateCase {
+        type Output = Array1<f64>;
+        type Param = Array1<f64>;
+        type Float = f64;
+        type Square = Array2<f64>;
+    }
+
+    #[derive(serde::Serialize)]
+    /// Mixer breaking its contract, either returning no parameter or failing to check termination
+    struct FaultyMixer {
+        terminate_fails: bool,
+    }
+
+    impl Mixer<TestCase> for FaultyMixer {
+        fn next_iter(
+            &mut self,
+            _op: &mut TestCase,
+            _state: &State<TestCase>,
+        ) -> Result<IterData<TestCase>, FixedPointError> {
+            Ok(IterData::new().cost(1.))
+        }
+
+        fn terminate(&mut self, _state: &State<TestCase>) -> Result<TerminationReason> {
+            match self.terminate_fails {
+                true => Err(InnerSolveFailed.into()),
+                false => Ok(TerminationReason::NotTerminated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_update_failed() {
+        let mut cost = FailingCase {
+            inner: TestCase::new(),
+            calls: 0,
+            fail_after: 5,
+        };
+        let init: Array1<f64> = Array1::ones(6);
+        let mut solver =
+            FixedPointSolver::new(LinearMixer::new(0.5), init).criterion(criterion(1e-12, 1000));
+
+        let error = solver.run(&mut cost).err().unwrap();
+        assert!(matches!(
+            error.get_error(),
+            FixedPointError::UpdateFailed { iteration: 5, .. }
+        ));
+        // The work done before the failure is returned with the error
+        assert_eq!(error.get_state().iter, 5);
+        assert!(error.get_state().best_cost.is_finite());
+        assert_eq!(error.get_best_param(), &solver.get_state().best_param);
+    }
+
+    #[test]
+    fn test_run_unimplemented_update() {
+        use miette::Diagnostic;
+
+        let init: Array1<f64> = Array1::ones(6);
+        let mut solver =
+            FixedPointSolver::new(LinearMixer::new(0.5), init).criterion(criterion(1e-12, 1000));
+
+        let error = solver.run(&mut NoUpdateCase).err().unwrap();
+        // The solver error reports as its cause, keeping the chain of sources
+        assert_eq!(error.to_string(), error.get_error().to_string());
+        assert_eq!(
+            error.help().map(|help| help.to_string()),
+            error.get_error().help().map(|help| help.to_string())
+        );
+        let source = std::error::Error::source(&error).unwrap();
+        assert_eq!(source.to_string(), "Unimplemented operation");
+
+        let error: FixedPointError = error.into();
+        match &error {
+            FixedPointError::UpdateFailed { source, .. } => assert!(matches!(
+                source.get_report().downcast_ref::<FixedPointError>(),
+                Some(FixedPointError::UnimplementedOperation)
+            )),
+            _ => panic!("unexpected error {:?}", error),
+        }
+    }
+
+    #[test]
+    fn test_run_numerical_divergence() {
+        let mut cost = DivergingCase {
+            inner: TestCase::new(),
+            calls: 0,
+            diverge_after: 3,
+        };
+        let init: Array1<f64> = Array1::ones(6);
+        let mut solver =
+            FixedPointSolver::new(LinearMixer::new(0.5), init).criterion(criterion(1e-12, 1000));
+
+        let error = solver.run(&mut cost).err().unwrap();
+        assert!(matches!(
+            error.get_error(),
+            FixedPointError::NumericalDivergence { iteration: 3 }
+        ));
+        assert!(!error.get_best_param().iter().any(|x| x.is_nan()));
+    }
+
+    #[test]
+    fn test_run_mixer_failed() {
+        let mut cost = FailingCase {
+            inner: TestCase::new(),
+            calls: 0,
+            fail_after: 1,
+        };
+        let init: Array1<f64> = Array1::ones(6);
+        let mixer = BacktrackingMixer::new(LinearMixer::new(0.5));
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+        let error = solver.run(&mut cost).err().unwrap();
+        assert!(matches!(
+            error.get_error(),
+            FixedPointError::MixerFailed { iteration: 0, .. }
+        ));
+    }
+
+    /// Problem refusing to evaluate the update at a point holding NaN
+    struct NaNGuardCase(TestCase);
+
+    impl FixedPointProblem for NaNGuardCase {
+        type Output = Array1<f64>;
+        type Param = Array1<f64>;
+        type Float = f64;
+        type Square = Array2<f64>;
+
+        fn update(&mut self, values: &Self::Param) -> Result<Self::Param> {
+            assert!(!values.iter().any(|x| x.is_nan()));
+            self.0.update(values)
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    /// Mixer proposing a point holding NaN
+    struct NaNProposalMixer;
+
+    impl Mixer<NaNGuardCase> for NaNProposalMixer {
+        fn next_iter(
+            &mut self,
+            _op: &mut NaNGuardCase,
+            state: &State<NaNGuardCase>,
+        ) -> Result<IterData<NaNGuardCase>, FixedPointError> {
+            Ok(IterData::new()
+                .cost(1.)
+                .param(Array1::from_elem(state.param.len(), f64::NAN)))
+        }
+    }
+
+    #[test]
+    fn test_trust_region_rejects_nan() {
+        let mut cost = NaNGuardCase(TestCase::new());
+        let init: Array1<f64> = Array1::ones(6);
+        let mixer = TrustRegionMixer::new(NaNProposalMixer);
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-8, 1000));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(result.converged());
+        // Every proposal is replaced by the relaxed linear step, evaluated once per iteration
+        let mixer = solver.get_mixer();
+        assert_eq!(mixer.get_rejections(), result.get_iterations());
+        assert_eq!(mixer.get_extra_evaluations(), result.get_iterations());
+    }
+
+    #[test]
+    fn test_run_unexpected_outcome() {
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let mixer = FaultyMixer {
+            terminate_fails: false,
+        };
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+        let error = solver.run(&mut cost).err().unwrap();
+        assert!(matches!(
+            error.get_error(),
+            FixedPointError::UnexpectedOutcome
+        ));
+        assert_eq!(error.get_state().iter, 0);
+    }
+
+    #[test]
+    fn test_run_termination_check_failed() {
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let mixer = FaultyMixer {
+            terminate_fails: true,
+        };
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+        let error = solver.run(&mut cost).err().unwrap();
+        match error.get_error() {
+            FixedPointError::TerminationCheckFailed { source, iteration } => {
+                assert_eq!(*iteration, 0);
+                assert_eq!(source.to_string(), "inner solve did not converge");
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_run_non_affine_update() {
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let mixer = GmresMixer::new().check_affine(true);
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+        let error = solver.run(&mut cost).err().unwrap();
+        assert!(matches!(
+            error.get_error(),
+            FixedPointError::NonAffineUpdate
+        ));
+    }
+
+    #[test]
+    fn test_run_invalid_spectral_bounds() {
+        let mut cost = AffineCase::new();
+        let init: Array1<f64> = Array1::zeros(6);
+        let mixer = ChebyshevMixer::new().spectral_bounds(-0.5, 1.5);
+        let mut solver = FixedPointSolver::new(mixer, init).criterion(criterion(1e-12, 1000));
+
+        let error = solver.run(&mut cost).err().unwrap();
+        assert!(matches!(
+            error.get_error(),
+            FixedPointError::InvalidSpectralBounds
+        ));
+    }
+
+    #[test]
+    fn test_run_checkpoint_failed() {
+        let path = std::env::temp_dir()
+            .join("conflux_directory_does_not_exist")
+            .join("checkpoint");
+        let mut cost = TestCase::new();
+        let init: Array1<f64> = Array1::ones(6);
+        let mut solver = FixedPointSolver::new(LinearMixer::new(0.5), init)
+            .criterion(criterion(1e-12, 1000))
+            .checkpoint(Checkpoint::new(&path, CheckpointFrequency::Every(2)));
+
+        let error = solver.run(&mut cost).err().unwrap();
+        assert!(matches!(
+            error.get_error(),
+            FixedPointError::CheckpointFailed { .. }
+        ));
+        assert_eq!(error.get_state().iter, 2);
+    }
+
+    #[test]
+    fn test_result_statistics() {
+        let mut cost = AffineCase::new();
+        let init: Array1<f64> = Array1::zeros(6);
+        let mut solver = FixedPointSolver::new(ChebyshevMixer::new(), init.clone())
+            .criterion(criterion(1e-10, 1000))
+            .record_cost_history();
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(result.converged());
+        let state = solver.get_state();
+        assert_eq!(result.get_iterations(), state.iter);
+        assert_eq!(result.get_evaluations(), state.evaluations);
+        // Estimating the spectral bounds takes evaluations beyond one per iteration
+        assert!(result.get_evaluations() > result.get_iterations());
+        assert_eq!(result.get_cost(), state.cost);
+
+        let history = result.get_cost_history().unwrap();
+        assert_eq!(history.len() as u64, result.get_iterations());
+        assert_eq!(history.last(), Some(&result.get_cost()));
+        let lowest = history.iter().copied().fold(f64::INFINITY, f64::min);
+        assert_eq!(result.get_best_cost(), lowest);
+
+        // Without recording, no history is kept
+        let mut solver =
+            FixedPointSolver::new(LinearMixer::new(0.5), init).criterion(criterion(1e-10, 1000));
+        assert!(solver.run(&mut cost).unwrap().get_cost_history().is_none());
+    }
+
+    #[test]
+    fn test_iteration_limit_result() {
+        let mut cost = NoisyAffineCase::new(1e-2, 7);
+        let init: Array1<f64> = Array1::zeros(6);
+        let mut solver =
+            FixedPointSolver::new(LinearMixer::new(0.5), init).criterion(criterion(1e-12, 30));
+
+        // Hitting the limit still returns the best iterate found
+        let result = solver.run(&mut cost).unwrap();
+        assert!(!result.converged());
+        assert_eq!(
+            result.get_termination_reason(),
+            TerminationReason::HitMaxIterations
+        );
+        assert_eq!(result.get_iterations(), 30);
+        assert!(result.get_best_cost() <= result.get_cost());
+        assert!(cost.inner.residual(&result.get_best_param()) < 1.);
+    }
+
+    #[test]
+    fn test_tune_no_viable_configuration() {
+        #[derive(Clone)]
+        struct AlwaysFailing;
+
+        impl FixedPointProblem for AlwaysFailing {
+            type Output = Array1<f64>;
+            type Param = Array1<f64>;
+            type Float = f64;
+            type Square = Array2<f64>;
+
+            fn update(&mut self, values: &Self::Param) -> Result<Self::Param> {
+                match values.iter().all(|x| *x == 0.) {
+                    true => Ok(Array1::ones(values.len())),
+                    false => Err(InnerSolveFailed.into()),
+                }
+            }
+        }
+
+        let init: Array1<f64> = Array1::zeros(6);
+        let tuner = Tuner::new(SearchSpace::new(vec![0.1, 0.5, 0.9]), 30);
+        let result = tuner.tune_linear(&AlwaysFailing, &init);
+        assert!(matches!(
+            result,
+            Err(FixedPointError::NoViableConfiguration)
+        ));
+    }
+
+    #[test]
+    fn test_step_matches_run() {
+        let init: Array1<f64> = Array1::ones(6);
+        let mixer = || Type1AndersonMixer::new(6);
+
+        let mut cost = TestCase::new();
+        let mut solver =
+            FixedPointSolver::new(mixer(), init.clone()).criterion(criterion(1e-12, 1000));
+        let expected = solver.run(&mut cost).unwrap();
+
+        let mut cost = TestCase::new();
+        let recorder = Arc::new(Mutex::new(CostRecorder::default()));
+        let mut stepped = FixedPointSolver::new(mixer(), init)
+            .criterion(criterion(1e-12, 1000))
+            .add_observer(recorder.clone());
+        let mut steps = 0;
+        while let Some(data) = stepped.step(&mut cost).unwrap() {
+            steps += 1;
+            assert_eq!(data.get_param(), Some(stepped.get_state().param.clone()));
+            // The run is only finished by the step which terminates
+            assert!(!recorder.lock().unwrap().finished);
+        }
+
+        assert!(recorder.lock().unwrap().finished);
+        assert_eq!(steps, solver.get_state().iter);
+        assert_eq!(stepped.get_state().param, expected.get_param());
+        // A terminated solver takes no further steps
+        assert!(stepped.step(&mut cost).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_steps_iterator() {
+        let mut cost = AffineCase::new();
+        let init: Array1<f64> = Array1::zeros(6);
+        let mut solver =
+            FixedPointSolver::new(LinearMixer::new(1.), init).criterion(criterion(1e-10, 1000));
+
+        let mut steps = solver.steps(&mut cost);
+        for (i, state) in steps.by_ref().take(5).enumerate() {
+            assert_eq!(state.unwrap().iter, i as u64 + 1);
+        }
+        // The problem changes part way through, and the solver follows it
+        steps.get_problem_mut().b *= 2.;
+        assert_eq!(
+            steps.by_ref().count() as u64 + 5,
+            steps.get_solver().get_state().iter
+        );
+        assert!(steps.next().is_none());
+
+        let solution = solver.get_state().param.clone();
+        assert!(cost.residual(&solution) < 1e-8);
+        assert_eq!(
+            solver.get_state().termination_reason,
+            TerminationReason::ToleranceBeaten
+        );
+    }
+
+    #[test]
+    fn test_steps_error() {
+        let mut cost = FailingCase {
+            inner: TestCase::new(),
+            calls: 0,
+            fail_after: 3,
+        };
+        let init: Array1<f64> = Array1::ones(6);
+        let mut solver =
+            FixedPointSolver::new(LinearMixer::new(0.5), init).criterion(criterion(1e-12, 1000));
+
+        let states: Vec<_> = solver.steps(&mut cost).collect();
+        assert_eq!(states.len(), 4);
+        assert!(states[..3].iter().all(|state| state.is_ok()));
+        assert!(matches!(
+            states[3],
+            Err(FixedPointError::UpdateFailed { iteration: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_recovery_from_nan() {
+        let mut cost = DivergingCase {
+            inner: TestCase::new(),
+            calls: 0,
+            diverge_after: 3,
+        };
+        let init: Array1<f64> = Array1::ones(6);
+        let mut solver = FixedPointSolver::new(LinearMixer::new(0.5), init)
+            .criterion(criterion(1e-12, 1000))
+            .recovery(Recovery::new(1));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(result.converged());
+        let recoveries = result.get_recoveries();
+        assert_eq!(recoveries.len(), 1);
+        assert_eq!(recoveries[0].cause, RecoveryCause::NumericalDivergence);
+        assert_eq!(recoveries[0].iteration, 3);
+        assert!(recoveries[0].restored_iteration <= 3);
+        assert!(recoveries[0].cost.is_nan());
+    }
+
+    #[test]
+    fn test_recovery_with_damping() {
+        let mut cost = AffineCase::new();
+        let init: Array1<f64> = Array1::zeros(6);
+        // Over-relaxation by 2.5 diverges, and damping brings it back into the convergent range
+        let mut solver = FixedPointSolver::new(LinearMixer::new(2.5), init)
+            .criterion(criterion(1e-10, 1000))
+            .recovery(Recovery::new(5).residual_growth(10.).damping(0.5));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert!(result.converged());
+        assert!(cost.residual(&result.get_param()) < 1e-8);
+        let recoveries = result.get_recoveries();
+        assert!(!recoveries.is_empty());
+        for recovery in recoveries {
+            assert_eq!(recovery.cause, RecoveryCause::ResidualGrowth);
+            assert!(recovery.restored_iteration < recovery.iteration);
+            assert!(recovery.cost > 10. * result.get_best_cost());
+        }
+        let damping = 0.5f64.powi(recoveries.len() as i32);
+        assert_eq!(solver.get_mixer().get_beta(), 2.5 * damping);
+    }
+
+    #[test]
+    fn test_recovery_limit() {
+        let mut cost = AffineCase::new();
+        let init: Array1<f64> = Array1::zeros(6);
+        // Without damping the rolled back iteration diverges again, until no recoveries remain
+        let mut solver = FixedPointSolver::new(LinearMixer::new(2.5), init)
+            .criterion(criterion(1e-10, 5000))
+            .recovery(Recovery::new(3).residual_growth(10.));
+
+        let error = solver.run(&mut cost).err().unwrap();
+        assert!(matches!(
+            error.get_error(),
+            FixedPointError::NumericalDivergence { .. }
+        ));
+        assert!(error.get_best_param().iter().all(|x| x.is_finite()));
+        assert_eq!(solver.get_mixer().get_beta(), 2.5);
+    }
+
+    #[test]
+    fn test_recovery_step_size() {
+        let mut cost = AffineCase::new();
+        let init: Array1<f64> = Array1::zeros(6);
+        // The rollback restores the parameter without taking a step, which must not read as one
+        let mut solver = FixedPointSolver::new(LinearMixer::new(2.5), init)
+            .criterion(Criterion::Any(vec![
+                Criterion::StepSize(1e-8),
+                Criterion::MaxIterations(50),
+            ]))
+            .recovery(Recovery::new(1).residual_growth(10.));
+
+        let result = solver.run(&mut cost).unwrap();
+        assert_eq!(result.get_recoveries().len(), 1);
+        assert!(!result.converged());
+        assert_eq!(
+            result.get_termination_reason(),
+            TerminationReason::HitMaxIterations
+        );
+    }
+
+    #[test]
+    fn test_tune_failing_trial() {
+        /// Affine problem whose update fails once the parameter grows large
+        #[derive(Clone)]
+        struct BoundedCase(AffineCase);
+
+        impl FixedPointProblem for BoundedCase {
+            type Output = Array1<f64>;
+            type Param = Array1<f64>;
+            type Float = f64;
+            type Square = Array2<f64>;
+
+            fn update(&mut self, values: &Self::Param) -> Result<Self::Param> {
+                match values.iter().any(|x| x.abs() > 1e3) {
+                    true => Err(InnerSolveFailed.into()),
+                    false => self.0.update(values),
+                }
+            }
+        }
+
+        let init: Array1<f64> = Array1::zeros(6);
+        // Over-relaxation by 2.5 diverges until the update fails, ending the trial's run in error
+        let tuner = Tuner::new(SearchSpace::new(vec![0.5, 2.5]), 100);
+        let result = tuner
+            .tune_linear(&BoundedCase(AffineCase::new()), &init)
+            .unwrap();
+
+        let trials = result.get_trials();
+        assert!(trials[0].rate.is_finite());
+        assert!(trials[1].residual.is_nan());
+        assert!(trials[1].rate.is_infinite());
+        assert!(trials[1].evaluations > 0);
+        assert_eq!(result.get_best().beta, 0.5);
+    }
 }
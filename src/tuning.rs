@@ -0,0 +1,350 @@
+/*!
+Tuning of mixer parameters by short trial solves
+
+Each candidate configuration drives a trial solve on a clone of the problem which is stopped once
+a budget of `update` calls is spent. Configurations are ranked by the average rate at which they
+reduced the true residual,
+
+rate = ln(||f(x_best) - x_best|| / ||f(x_0) - x_0||) / evaluations
+
+and the fastest is returned. Configurations and trial records are serialisable, so a tuned
+configuration can be stored and reused for later solves of similar problems.
+*/
+
+use crate::prelude::*;
+use crate::solvers::{anderson::Type1AndersonMixer, linear::LinearMixer};
+use miette::Result;
+use rand::{seq::index, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::panic::{self, AssertUnwindSafe};
+use tracing::debug;
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+/// A set of mixer parameters
+///
+/// Parameters which are not part of the search space are `None`, and the mixer default is used
+pub struct TuningConfig<F> {
+    /// Relaxation parameter
+    pub beta: F,
+    /// Memory size
+    pub memory: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Candidate values for each tunable parameter
+pub struct SearchSpace<F> {
+    beta: Vec<F>,
+    memory: Vec<usize>,
+}
+
+impl<F: FPFloat> SearchSpace<F> {
+    /// Generate a search space over the given relaxation parameters
+    pub fn new(beta: Vec<F>) -> Self {
+        SearchSpace {
+            beta,
+            memory: vec![],
+        }
+    }
+
+    /// Factory method to set the candidate memory sizes
+    pub fn memory(mut self, memory: Vec<usize>) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// All combinations of the candidate values
+    fn grid(&self) -> Vec<TuningConfig<F>> {
+        let memory: Vec<Option<usize>> = match self.memory.is_empty() {
+            true => vec![None],
+            false => self.memory.iter().copied().map(Some).collect(),
+        };
+
+        let mut configs = vec![];
+        for &beta in &self.beta {
+            for &memory in &memory {
+                configs.push(TuningConfig { beta, memory });
+            }
+        }
+        configs
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+/// Strategy for choosing which configurations to trial
+pub enum SearchStrategy {
+    /// Trial every combination in the search space
+    Grid,
+    /// Trial the given number of combinations, drawn without replacement from a seeded generator
+    Random {
+        /// Number of configurations to trial
+        samples: usize,
+        /// Seed for the random number generator
+        seed: u64,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Record of a single trial solve
+pub struct Trial<F> {
+    /// Configuration used for the trial
+    pub config: TuningConfig<F>,
+    /// Number of `update` calls made by the mixer
+    pub evaluations: usize,
+    /// True residual at the best parameter found, `NaN` if the trial failed
+    pub residual: f64,
+    /// Average logarithmic residual reduction per evaluation, infinite if the trial failed
+    pub rate: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Result of a tuning run
+pub struct TuningResult<F> {
+    /// Configuration which reduced the residual fastest
+    best: TuningConfig<F>,
+
+    /// Every trial, in the order they were run
+    trials: Vec<Trial<F>>,
+}
+
+impl<F: Clone> TuningResult<F> {
+    /// Return the configuration which reduced the residual fastest
+    pub fn get_best(&self) -> TuningConfig<F> {
+        self.best.clone()
+    }
+
+    /// Return the records of all trials
+    pub fn get_trials(&self) -> &[Trial<F>] {
+        &self.trials
+    }
+}
+
+/// Wrapper counting `update` calls, which stops the solver once its budget is spent
+///
+/// Updates requested after the budget is spent are refused, so a trial never overshoots it
+pub struct BudgetedProblem<P> {
+    inner: P,
+    budget: usize,
+    evaluations: usize,
+    token: CancellationToken,
+}
+
+impl<P: FixedPointProblem> FixedPointProblem for BudgetedProblem<P> {
+    type Output = P::Output;
+    type Param = P::Param;
+    type Float = P::Float;
+    type Square = P::Square;
+
+    fn update(&mut self, values: &Self::Param) -> Result<Self::Param> {
+        if self.evaluations >= self.budget {
+            self.token.cancel();
+            return Err(FixedPointError::BudgetExhausted {
+                budget: self.budget,
+            }
+            .into());
+        }
+        self.evaluations += 1;
+        if self.evaluations >= self.budget {
+            self.token.cancel();
+        }
+        self.inner.update(values)
+    }
+
+    fn error_vector(
+        &mut self,
+        values: &Self::Param,
+        output: &Self::Param,
+    ) -> Result<Option<Self::Param>> {
+        self.inner.error_vector(values, output)
+    }
+
+    fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.inner.set_cancellation_token(token.clone());
+        self.token = token;
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Tunes mixer parameters by running budgeted trial solves
+pub struct Tuner<F> {
+    space: SearchSpace<F>,
+    strategy: SearchStrategy,
+    budget: usize,
+}
+
+impl<F: FPFloat + FPIntof64> Tuner<F> {
+    /// Generate a grid search over the space, allowing each trial the given number of updates
+    pub fn new(space: SearchSpace<F>, budget: usize) -> Self {
+        Tuner {
+            space,
+            strategy: SearchStrategy::Grid,
+            budget,
+        }
+    }
+
+    /// Factory method to set the search strategy
+    pub fn strategy(mut self, strategy: SearchStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Configurations to trial under the chosen strategy
+    fn candidates(&self) -> Vec<TuningConfig<F>> {
+        let grid = self.space.grid();
+        match self.strategy {
+            SearchStrategy::Grid => grid,
+            SearchStrategy::Random { samples, seed } => {
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                index::sample(&mut rng, grid.len(), samples.min(grid.len()))
+                    .into_iter()
+                    .map(|i| grid[i].clone())
+                    .collect()
+            }
+        }
+    }
+
+    /// Tune the mixer built from each configuration by the closure
+    ///
+    /// Trials only stop on a residual of machine precision, so the budget ends each trial
+    pub fn tune<P, M, B>(
+        &self,
+        op: &P,
+        init: &P::Param,
+        build: B,
+    ) -> Result<TuningResult<F>, FixedPointError>
+    where
+        P: FixedPointProblem<Float = F> + Clone,
+        P::Param: FPSub<P::Param, P::Param> + FPNorm<F>,
+        M: Mixer<BudgetedProblem<P>>,
+        B: Fn(&TuningConfig<F>) -> M,
+    {
+        let initial_residual = true_residual(&mut op.clone(), init)?.max(f64::MIN_POSITIVE);
+
+        let mut trials = vec![];
+        for config in self.candidates() {
+            let mut problem = BudgetedProblem {
+                inner: op.clone(),
+                budget: self.budget,
+                evaluations: 0,
+                token: CancellationToken::new(),
+            };
+            let mut solver = FixedPointSolver::new(build(&config), init.clone())
+                .criterion(Criterion::AbsoluteResidual(F::epsilon()));
+
+            // A failing trial is recorded and the search continues
+            let completed = panic::catch_unwind(AssertUnwindSafe(|| solver.run(&mut problem)))
+                .is_ok_and(|result| result.is_ok());
+            let residual = match completed {
+                true => true_residual(&mut problem.inner, &solver.get_state().best_param).ok(),
+                false => None,
+            };
+
+            let (residual, rate) = match residual {
+                Some(r) if problem.evaluations > 0 => (
+                    r,
+                    (r.max(f64::MIN_POSITIVE) / initial_residual).ln() / problem.evaluations as f64,
+                ),
+                _ => (f64::NAN, f64::INFINITY),
+            };
+            debug!(
+                beta = %config.beta,
+                evaluations = problem.evaluations,
+                residual,
+                rate,
+                "Completed tuning trial"
+            );
+
+            trials.push(Trial {
+                config,
+                evaluations: problem.evaluations,
+                residual,
+                rate,
+            });
+        }
+
+        let best = trials
+            .iter()
+            .filter(|trial| trial.rate.is_finite())
+            .min_by(|a, b| a.rate.partial_cmp(&b.rate).unwrap())
+            .ok_or(FixedPointError::NoViableConfiguration)?
+            .config
+            .clone();
+
+        Ok(TuningResult { best, trials })
+    }
+
+    /// Tune the relaxation parameter of a `LinearMixer`
+    pub fn tune_linear<P>(
+        &self,
+        op: &P,
+        init: &P::Param,
+    ) -> Result<TuningResult<F>, FixedPointError>
+    where
+        P: FixedPointProblem<Float = F> + Clone,
+        P::Param: FPMul<F, P::Param>
+            + FPAdd<P::Param, P::Param>
+            + FPSub<P::Param, P::Param>
+            + FPNorm<F>
+            + FPHoldsNaN,
+    {
+        self.tune(op, init, |config| LinearMixer::new(config.beta))
+    }
+
+    /// Tune the relaxation and memory of a `Type1AndersonMixer`
+    pub fn tune_anderson<P>(
+        &self,
+        op: &P,
+        init: &P::Param,
+    ) -> Result<TuningResult<F>, FixedPointError>
+    where
+        P: FixedPointProblem<Float = F> + Clone,
+        P::Param: FPFromZeros
+            + FPSub<P::Param, P::Param>
+            + FPNorm<F>
+            + FPMul<F, P::Param>
+            + FPAdd<P::Param, P::Param>
+            + FPDiv<F, P::Param>
+            + FPTranspose
+            + FPDot<P::Param, P::Square>
+            + FPDot<P::Square, P::Param>
+            + FPDot<P::Param, F>
+            + FPInto2D<P::Square>
+            + FPDimension
+            + FPHoldsNaN
+            + std::fmt::Debug,
+        P::Square: FPEye
+            + FPFromZeros
+            + FPEmpty
+            + FPTranspose
+            + FPDot<P::Square, P::Square>
+            + FPDot<P::Param, P::Param>
+            + FPStack<P::Param>,
+    {
+        self.tune(op, init, |config| {
+            let mut mixer: Type1AndersonMixer<F, BudgetedProblem<P>> =
+                Type1AndersonMixer::new(init.dimension()).beta(config.beta);
+            if let Some(memory) = config.memory {
+                mixer = mixer.memory(memory as u64);
+            }
+            mixer
+        })
+    }
+}
+
+/// Norm of the residual f(x) - x, failing if the update fails or the residual is not finite
+fn true_residual<P>(op: &mut P, values: &P::Param) -> Result<f64, FixedPointError>
+where
+    P: FixedPointProblem,
+    P::Param: FPSub<P::Param, P::Param> + FPNorm<P::Float>,
+    P::Float: FPIntof64,
+{
+    let output = match op.update(values) {
+        Ok(x) => x,
+        Err(e) => return Err(FixedPointError::update_failed(e, 0, values.norm())),
+    };
+    let residual = output.sub(values).norm().cast_f64();
+    match residual.is_finite() {
+        true => Ok(residual),
+        false => Err(FixedPointError::NumericalDivergence { iteration: 0 }),
+    }
+}